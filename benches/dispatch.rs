@@ -0,0 +1,31 @@
+use lingo::dictionary::{Charset, Dictionary, Word};
+use lingo::strategy::{FrequencyScorer, GuessStrategy, ScoreMode};
+
+use std::fs::File;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 50;
+
+fn total<S: GuessStrategy + ?Sized>(strategy: &S, words: &[Word]) -> f64 {
+    words.iter().map(|w| strategy.score(w)).sum()
+}
+
+fn bench(name: &str, words: &[Word], score: impl Fn(&[Word]) -> f64) {
+    let start = Instant::now();
+    let mut sum = 0.0;
+    for _ in 0..ITERATIONS {
+        sum += score(words);
+    }
+
+    println!("{}: {} us/iter ({})", name, start.elapsed().as_micros() / ITERATIONS as u128, sum);
+}
+
+fn main() {
+    let dictionary = Dictionary::from_file(File::open("words_alpha.txt").expect("Error opening dict file"), &Charset::default());
+    let scorer = FrequencyScorer::new(&dictionary, ScoreMode::Combined, 0.0);
+    let dynamic: &dyn GuessStrategy = &scorer;
+
+    println!("scoring {} words", dictionary.words.len());
+    bench("enum_dispatch", &dictionary.words, |words| total(&scorer, words));
+    bench("dyn_dispatch", &dictionary.words, |words| total(dynamic, words));
+}
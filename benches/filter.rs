@@ -0,0 +1,48 @@
+use lingo::clue::{Clue, Hint};
+use lingo::dictionary::{Charset, Dictionary};
+
+use std::fs::File;
+use std::time::Instant;
+
+const FIXTURE_WORDS: usize = 2000;
+const ITERATIONS: u32 = 500;
+
+// The filter as it was before switching to `retain`.
+fn clone_filter(dictionary: &mut Dictionary, clue: &Clue) {
+    dictionary.words = dictionary.words.clone().into_iter()
+        .filter(|w| w.has(clue))
+        .collect();
+}
+
+fn bench(name: &str, fixture: &Dictionary, clue: &Clue, filter: impl Fn(&mut Dictionary, &Clue)) {
+    let mut remaining = 0;
+    let mut elapsed = 0;
+
+    for _ in 0..ITERATIONS {
+        let mut dictionary = fixture.clone();
+
+        let start = Instant::now();
+        filter(&mut dictionary, clue);
+        elapsed += start.elapsed().as_nanos();
+
+        remaining = dictionary.words.len();
+    }
+
+    println!("{}: {} ns/iter ({} words left)", name, elapsed / ITERATIONS as u128, remaining);
+}
+
+fn main() {
+    let mut fixture = Dictionary::from_file(File::open("words_alpha.txt").expect("Error opening dict file"), &Charset::default());
+    fixture.words.truncate(FIXTURE_WORDS);
+
+    // 'e' is present but not in the second position, a typical yellow tile.
+    let clue = Clue {
+        c: 'e',
+        occur: 1,
+        max: None,
+        hints: vec![Hint::Maybe, Hint::No, Hint::Maybe, Hint::Maybe, Hint::Maybe],
+    };
+
+    bench("clone_filter", &fixture, &clue, clone_filter);
+    bench("retain_filter", &fixture, &clue, |d, c| d.filter(c));
+}
@@ -0,0 +1,33 @@
+use lingo::dictionary::{self, Charset, Dictionary};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::Instant;
+
+const FILES: usize = 5;
+
+fn main() {
+    let data = fs::read_to_string("words_alpha.txt").expect("Error opening dict file");
+
+    let mut paths = BTreeMap::new();
+    for idx in 0..FILES {
+        let path = std::env::temp_dir().join(format!("lingo-bench-{}.txt", idx));
+        fs::write(&path, &data).expect("Error writing fixture");
+        paths.insert(format!("dict{}", idx), path.to_string_lossy().into_owned());
+    }
+
+    let start = Instant::now();
+    for path in paths.values() {
+        Dictionary::load(path, &Charset::default()).expect("Error loading fixture");
+    }
+    println!("sequential: {} ms for {} files", start.elapsed().as_millis(), FILES);
+
+    let start = Instant::now();
+    let loaded = dictionary::load_named(&paths, &Charset::default());
+    println!("parallel: {} ms for {} files", start.elapsed().as_millis(), FILES);
+    assert!(loaded.values().all(|d| d.is_ok()));
+
+    for path in paths.values() {
+        let _ = fs::remove_file(path);
+    }
+}
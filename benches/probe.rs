@@ -0,0 +1,35 @@
+use lingo::config::Config;
+use lingo::dictionary::{Charset, Dictionary};
+use lingo::solver::Solver;
+
+use std::fs::File;
+use std::time::Instant;
+
+const LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+fn bench(name: &str, config: &Config, dictionary: &Dictionary) {
+    let mut solver = Solver::new(config, dictionary.clone(), dictionary.clone());
+
+    let start = Instant::now();
+    let mut guesses = Vec::new();
+    for c in LETTERS.chars() {
+        solver.reset(c);
+        guesses.push(solver.get_guess());
+    }
+
+    println!("{}: {} us/guess ({} probes, opens {})", name, start.elapsed().as_micros() / LETTERS.len() as u128,
+        solver.probe_words.words.len(), guesses[..3].join(" "));
+}
+
+fn main() {
+    let dictionary = Dictionary::from_file(File::open("words_alpha.txt").expect("Error opening dict file"), &Charset::default());
+
+    bench("full_pool", &Config::default(), &dictionary);
+    for &limit in &[4000, 1000] {
+        let config = Config {
+            probe_pool: Some(limit),
+            ..Config::default()
+        };
+        bench(&format!("probe_pool_{}", limit), &config, &dictionary);
+    }
+}
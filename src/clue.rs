@@ -0,0 +1,515 @@
+use crate::error::{FieldError, LingoError};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+// Splits a cased share string into a guess and its feedback. An uppercase
+// letter is green, a lowercase letter is yellow and an absent letter is
+// written lowercase after a '-', so "Cr-aN-e" is the guess "crane" with
+// feedback "cwncn".
+pub fn decode_cased(input: &str, word_len: usize) -> Result<(String, String), LingoError> {
+    let mut guess = String::new();
+    let mut feedback = String::new();
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '-' {
+            match chars.next() {
+                Some(c) if c.is_ascii_lowercase() => {
+                    guess.push(c);
+                    feedback.push('n');
+                },
+                _ => return Err(cased_error("'-' must be followed by a lowercase letter")),
+            }
+        }
+        else if c.is_ascii_uppercase() {
+            guess.push(c.to_ascii_lowercase());
+            feedback.push('c');
+        }
+        else if c.is_ascii_lowercase() {
+            guess.push(c);
+            feedback.push('w');
+        }
+        else {
+            return Err(cased_error(format!("unexpected character '{}'", c)));
+        }
+    }
+
+    if guess.len() != word_len {
+        return Err(cased_error(format!("must decode to a {} letter guess", word_len)));
+    }
+
+    Ok((guess, feedback))
+}
+
+fn cased_error(message: impl Into<String>) -> LingoError {
+    LingoError::Validation(vec![FieldError::new("cased", message)])
+}
+
+fn tighter_max(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+fn compact_error(message: impl Into<String>) -> LingoError {
+    LingoError::Validation(vec![FieldError::new("constraints", message)])
+}
+
+// What a clue says about its letter at one position of the answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Hint {
+    // The letter is at this position.
+    Yes,
+    // The letter is not at this position.
+    No,
+    // The letter may or may not be at this position. A fully yellow letter
+    // gets Maybe everywhere it wasn't guessed, so it can land in any of
+    // those positions as long as `occur` is met.
+    Maybe,
+    // Only used while building a clue.
+    Unset,
+}
+
+// How a reset treats the letter it is given.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FirstLetter {
+    // The answer starts with the letter, as in Lingo.
+    Green,
+    // The letter is somewhere in the answer.
+    Present,
+    // The letter says nothing about the answer.
+    Ignore,
+}
+
+impl FirstLetter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FirstLetter::Green => "green",
+            FirstLetter::Present => "present",
+            FirstLetter::Ignore => "ignore",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<FirstLetter> {
+        match name {
+            "green" => Some(FirstLetter::Green),
+            "present" => Some(FirstLetter::Present),
+            "ignore" => Some(FirstLetter::Ignore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Clue {
+    pub c: char,
+    // The least number of times the letter appears in the answer.
+    pub occur: u32,
+    // The most times it appears, once a gray tile has capped it.
+    pub max: Option<u32>,
+    pub hints: Vec<Hint>
+}
+
+impl Clue {
+    // The clue a reset with `c` gives, if any.
+    pub fn first_letter(c: char, mode: FirstLetter, answer_len: usize) -> Option<Clue> {
+        let mut hints = vec![Hint::Maybe; answer_len];
+
+        match mode {
+            FirstLetter::Green => hints[0] = Hint::Yes,
+            FirstLetter::Present => {},
+            FirstLetter::Ignore => return None,
+        }
+
+        Some(Clue {
+            c,
+            occur: 1,
+            max: None,
+            hints,
+        })
+    }
+
+    // The guess may be shorter or longer than the answer, for variants that
+    // probe a six letter answer with five letter words (or the reverse).
+    // Feedback at position i still describes answer position i; a tile past
+    // the end of the answer only says whether the letter is present, and
+    // answer positions past the end of the guess stay open. No route takes
+    // such a guess yet, since `validate_lengths` holds guesses to WORD_LEN.
+    pub fn from_input(guess: &str, inp: &str, answer_len: usize) -> Vec<Clue> {
+        let mut chars: Vec<char> = guess.chars().collect();
+        chars.sort();
+        chars.dedup();
+
+        let input_chars: Vec<char> = inp.chars().collect();
+
+        let mut clues = Vec::new();
+        
+        for c in chars {
+            // Positions are counted in chars, so an extra charset with
+            // multi-byte characters still lines up with the feedback.
+            // Letters compare exactly, so under a case-sensitive charset 'P'
+            // and 'p' are different letters.
+            let matches = guess.chars().enumerate().filter(|&(_, g)| g == c);
+            let mut hints = vec![Hint::Unset; answer_len];
+
+            let mut correct = 0;
+            let mut wrong_place = 0;
+            let mut wrong = 0;
+
+            for (idx, _) in matches {
+                if idx >= answer_len {
+                    if input_chars[idx] == 'c' || input_chars[idx] == 'w' {
+                        wrong_place += 1;
+                    }
+                    else {
+                        wrong += 1;
+                    }
+                }
+                else if input_chars[idx] == 'c' {
+                    correct += 1;
+                    hints[idx] = Hint::Yes;
+                }
+                else if input_chars[idx] == 'w' {
+                    wrong_place += 1;
+                    hints[idx] = Hint::No;
+                }
+                else {
+                    wrong += 1;
+                    hints[idx] = Hint::No;
+                }
+            }
+
+            // Every green or yellow tile of the letter is a separate
+            // occurrence in the answer, and a gray tile means there are no
+            // more than that.
+            let occur = correct + wrong_place;
+            let max = if wrong > 0 { Some(occur) } else { None };
+
+            // With every copy accounted for by greens, the letter can't be
+            // anywhere else. A yellow copy can still be in any position it
+            // wasn't guessed at, however many gray copies there are.
+            let replace = if wrong > 0 && wrong_place == 0 { Hint::No } else { Hint::Maybe };
+            for hint in &mut hints {
+                if *hint == Hint::Unset {
+                    *hint = replace.clone();
+                }
+            }
+
+            clues.push(Clue {
+                c,
+                occur,
+                max,
+                hints,
+            });
+        }
+
+        clues
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LetterConstraint {
+    pub min: u32,
+    pub max: Option<u32>,
+    // Positions known to hold the letter.
+    pub greens: Vec<usize>,
+    pub forbidden: Vec<usize>,
+}
+
+// Everything learned about each letter so far, merged across turns.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintSet {
+    pub letters: BTreeMap<char, LetterConstraint>,
+}
+
+impl ConstraintSet {
+    pub fn add(&mut self, clue: &Clue) {
+        let letter = self.letters.entry(clue.c).or_default();
+
+        if clue.occur > letter.min {
+            letter.min = clue.occur;
+        }
+        letter.max = tighter_max(letter.max, clue.max);
+        for (idx, hint) in clue.hints.iter().enumerate() {
+            if *hint == Hint::No && !letter.forbidden.contains(&idx) {
+                letter.forbidden.push(idx);
+            }
+            if *hint == Hint::Yes && !letter.greens.contains(&idx) {
+                letter.greens.push(idx);
+            }
+        }
+        letter.forbidden.sort();
+        letter.greens.sort();
+
+        self.compact();
+    }
+
+    // Drops whatever another constraint already implies, so the set stays
+    // one small entry per letter however many turns it took to learn. A
+    // position is only forbidden while nothing else is known there: once
+    // another letter is green at it, or every allowed copy of the letter is
+    // placed, the position says nothing new. Letters left with no
+    // constraint at all are removed.
+    fn compact(&mut self) {
+        let greens: Vec<(char, usize)> = self.letters.iter()
+            .flat_map(|(&c, letter)| letter.greens.iter().map(move |&idx| (c, idx)))
+            .collect();
+
+        for (&c, letter) in self.letters.iter_mut() {
+            if letter.max == Some(letter.greens.len() as u32) {
+                letter.forbidden.clear();
+            }
+            letter.forbidden.retain(|&idx| !greens.iter().any(|&(green, green_idx)| green != c && green_idx == idx));
+        }
+
+        self.letters.retain(|_, letter| {
+            letter.min > 0 || letter.max.is_some() || !letter.greens.is_empty() || !letter.forbidden.is_empty()
+        });
+    }
+
+    // A clue per letter enforcing everything known about it.
+    pub fn clues(&self, answer_len: usize) -> Vec<Clue> {
+        self.letters.iter()
+            .map(|(&c, letter)| {
+                let mut hints = vec![Hint::Maybe; answer_len];
+                for &idx in &letter.forbidden {
+                    hints[idx] = Hint::No;
+                }
+                for &idx in &letter.greens {
+                    hints[idx] = Hint::Yes;
+                }
+
+                Clue {
+                    c,
+                    occur: letter.min,
+                    max: letter.max,
+                    hints,
+                }
+            })
+            .collect()
+    }
+
+    // Encodes the constraints for a query string. Each letter is written as
+    // the letter and its minimum count, then 'm' and its maximum count, 'g'
+    // and its green positions and 'x' and its forbidden positions when it
+    // has any, with letters joined by '.'. So "a1g0.e1m1x3.r0m0" is an
+    // answer starting with 'a', with exactly one 'e', which isn't fourth,
+    // and no 'r'.
+    pub fn to_compact_string(&self) -> String {
+        let entries: Vec<String> = self.letters.iter()
+            .map(|(c, letter)| {
+                let mut entry = format!("{}{}", c, letter.min);
+                if let Some(max) = letter.max {
+                    entry += &format!("m{}", max);
+                }
+                if !letter.greens.is_empty() {
+                    entry.push('g');
+                    entry.extend(letter.greens.iter().map(|idx| idx.to_string()));
+                }
+                if !letter.forbidden.is_empty() {
+                    entry.push('x');
+                    entry.extend(letter.forbidden.iter().map(|idx| idx.to_string()));
+                }
+                entry
+            })
+            .collect();
+
+        entries.join(".")
+    }
+
+    // Parses `to_compact_string`, for answers of `answer_len` letters.
+    pub fn from_compact_string(input: &str, answer_len: usize) -> Result<ConstraintSet, LingoError> {
+        let mut set = ConstraintSet::default();
+        if input.is_empty() {
+            return Ok(set);
+        }
+
+        for entry in input.split('.') {
+            let mut chars = entry.chars();
+            let c = chars.next().ok_or_else(|| compact_error("empty letter entry"))?;
+            if set.letters.contains_key(&c) {
+                return Err(compact_error(format!("{} appears twice", c)));
+            }
+
+            let rest: String = chars.collect();
+            let min_len = rest.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(rest.len());
+            let min = rest[..min_len].parse()
+                .map_err(|_| compact_error(format!("{} has no minimum count", c)))?;
+
+            let mut letter = LetterConstraint {
+                min,
+                ..LetterConstraint::default()
+            };
+            let mut section: Option<&mut Vec<usize>> = None;
+            let mut chars = rest[min_len..].chars();
+            while let Some(ch) = chars.next() {
+                match ch {
+                    'm' if letter.max.is_none() => {
+                        let max = chars.next()
+                            .and_then(|ch| ch.to_digit(10))
+                            .filter(|&max| max >= letter.min)
+                            .ok_or_else(|| compact_error(format!("{} has an invalid maximum count", c)))?;
+                        letter.max = Some(max);
+                        section = None;
+                    },
+                    'g' => section = Some(&mut letter.greens),
+                    'x' => section = Some(&mut letter.forbidden),
+                    _ => {
+                        let idx = ch.to_digit(10)
+                            .map(|d| d as usize)
+                            .filter(|&idx| idx < answer_len)
+                            .ok_or_else(|| compact_error(format!("{} has an invalid position {}", c, ch)))?;
+                        match section.as_mut() {
+                            Some(positions) if !positions.contains(&idx) => positions.push(idx),
+                            Some(_) => return Err(compact_error(format!("{} repeats position {}", c, idx))),
+                            None => return Err(compact_error(format!("{} has a position outside a section", c))),
+                        }
+                    },
+                }
+            }
+
+            if letter.greens.iter().any(|idx| letter.forbidden.contains(idx)) {
+                return Err(compact_error(format!("{} is both green and forbidden at a position", c)));
+            }
+            letter.greens.sort();
+            letter.forbidden.sort();
+            set.letters.insert(c, letter);
+        }
+
+        set.compact();
+        Ok(set)
+    }
+
+    // Tightens a clue with the positions and occurrences already known for
+    // its letter, so `Word::has` enforces them as well.
+    pub fn merge(&self, clue: &Clue) -> Clue {
+        let mut merged = clue.clone();
+
+        if let Some(letter) = self.letters.get(&clue.c) {
+            if letter.min > merged.occur {
+                merged.occur = letter.min;
+            }
+            merged.max = tighter_max(merged.max, letter.max);
+            for &idx in &letter.forbidden {
+                if merged.hints[idx] == Hint::Maybe {
+                    merged.hints[idx] = Hint::No;
+                }
+            }
+        }
+
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{Word, WORD_LEN};
+
+    fn word(s: &str) -> Word {
+        Word::new(s, WORD_LEN).unwrap()
+    }
+
+    fn clue_for(c: char, guess: &str, feedback: &str, answer_len: usize) -> Clue {
+        Clue::from_input(guess, feedback, answer_len).into_iter()
+            .find(|clue| clue.c == c)
+            .unwrap()
+    }
+
+    #[test]
+    fn yellow_positions_accumulate_across_turns() {
+        let mut constraints = ConstraintSet::default();
+        for clue in Clue::from_input("abbbb", "wnnnn", WORD_LEN) {
+            let clue = constraints.merge(&clue);
+            constraints.add(&clue);
+        }
+        let merged = constraints.merge(&clue_for('a', "bbabb", "nnwnn", WORD_LEN));
+
+        assert_eq!(merged.hints[0], Hint::No);
+        assert_eq!(merged.hints[2], Hint::No);
+        assert!(!word("apple").has(&merged));
+        assert!(!word("chant").has(&merged));
+        assert!(word("cants").has(&merged));
+    }
+
+    #[test]
+    fn shorter_guess_sizes_hints_by_answer() {
+        let clues = Clue::from_input("abcde", "cnnnn", 6);
+        assert!(clues.iter().all(|clue| clue.hints.len() == 6));
+
+        let a = clue_for('a', "abcde", "cnnnn", 6);
+        assert_eq!(a.hints[0], Hint::Yes);
+        assert_eq!(a.hints[5], Hint::Maybe);
+        let b = clue_for('b', "abcde", "cnnnn", 6);
+        assert_eq!(b.max, Some(0));
+    }
+
+    #[test]
+    fn longer_guess_only_counts_tiles_past_the_answer() {
+        let f = clue_for('f', "abcdef", "nnnnnw", 5);
+        assert_eq!(f.hints.len(), 5);
+        assert_eq!(f.occur, 1);
+        assert!(f.hints.iter().all(|hint| *hint == Hint::Maybe));
+
+        let e = clue_for('e', "abcdef", "nnnnnn", 5);
+        assert_eq!(e.max, Some(0));
+    }
+
+    #[test]
+    fn fully_yellow_letter_may_sit_anywhere_else() {
+        let e = clue_for('e', "ebbbb", "wnnnn", WORD_LEN);
+        assert_eq!(e.hints, vec![Hint::No, Hint::Maybe, Hint::Maybe, Hint::Maybe, Hint::Maybe]);
+        assert_eq!(e.max, None);
+
+        assert!(word("there").has(&e));
+        assert!(!word("eaten").has(&e));
+        assert!(!word("chant").has(&e));
+    }
+
+    #[test]
+    fn repeated_yellow_letter_needs_every_copy() {
+        let e = clue_for('e', "eebbb", "wwnnn", WORD_LEN);
+        assert_eq!(e.occur, 2);
+
+        assert!(word("there").has(&e));
+        assert!(!word("cleat").has(&e));
+        assert!(word("sheep").has(&e));
+        assert!(!word("eerie").has(&e));
+    }
+
+    fn learn(constraints: &mut ConstraintSet, guess: &str, feedback: &str) {
+        for clue in Clue::from_input(guess, feedback, WORD_LEN) {
+            constraints.add(&clue);
+        }
+    }
+
+    #[test]
+    fn later_green_drops_a_forbidden_position() {
+        let mut constraints = ConstraintSet::default();
+        learn(&mut constraints, "abbbb", "wnnnn");
+        assert_eq!(constraints.letters[&'a'].forbidden, vec![0]);
+
+        // Another letter green where 'a' was yellow.
+        learn(&mut constraints, "sbbbb", "cnnnn");
+        assert!(constraints.letters[&'a'].forbidden.is_empty());
+        assert_eq!(constraints.letters[&'a'].min, 1);
+    }
+
+    #[test]
+    fn placing_every_copy_drops_forbidden_positions() {
+        let mut constraints = ConstraintSet::default();
+        learn(&mut constraints, "abbbb", "wnnnn");
+        learn(&mut constraints, "bbaab", "nncnn");
+
+        let a = &constraints.letters[&'a'];
+        assert_eq!(a.greens, vec![2]);
+        assert_eq!(a.max, Some(1));
+        assert!(a.forbidden.is_empty());
+        assert_eq!(constraints.to_compact_string(), "a1m1g2.b0m0");
+    }
+}
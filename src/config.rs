@@ -0,0 +1,271 @@
+use crate::dictionary::Charset;
+use crate::error::{FieldError, LingoError};
+use crate::solver::ProbeSource;
+use crate::strategy::ScoreMode;
+use crate::validate::check;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+
+const CONFIG_PATH: &str = "lingo.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub dictionary: String,
+    /// Characters besides a-z that dictionary words and guesses may contain,
+    /// such as "'-". Dictionary lines with anything else are skipped.
+    pub extra_chars: String,
+    /// More word lists by name, loaded in parallel at startup and chosen with
+    /// `/dictionary/{name}`.
+    pub dictionaries: BTreeMap<String, String>,
+    /// A word list for each word length, such as `{"5": "words5.txt", "6":
+    /// "words6.txt"}`, each keeping only the words of its length. The game
+    /// plays with the 5 letter list in place of `dictionary`, so it has to
+    /// be there, and `/lengths/{length}/{word}` checks words against the
+    /// others.
+    pub length_dictionaries: BTreeMap<usize, String>,
+    /// Only the first N dictionary words of the right length are used as
+    /// answers. This assumes the dictionary is ordered from most to least
+    /// common; guesses still come from the whole dictionary.
+    pub answer_limit: Option<usize>,
+    /// A word list, such as one written by `lingo hard-words`, to use as the
+    /// answers instead of the dictionary. Words missing from the dictionary
+    /// are skipped, and `answer_limit` applies to what's left.
+    pub answers: Option<String>,
+    /// A file with both word lists: the answers, a blank line, then the
+    /// other allowed guesses, all of them 5 letters. It replaces
+    /// `dictionary` and `answers`, and `answer_limit` still applies.
+    pub combined: Option<String>,
+    /// Guesses returned in order for the first turns, whatever the feedback,
+    /// before the strategy takes over.
+    pub openers: Vec<String>,
+    /// With this many candidates or fewer, guess one of the candidates
+    /// rather than a probe that can't win this turn. 0 always probes.
+    pub endgame_threshold: usize,
+    /// Only suggest a probe while more than this many candidates remain,
+    /// and otherwise the best-ranked candidate. Unlike `endgame_threshold`,
+    /// which ranks only the candidates, this leaves the ranking of every
+    /// guess alone, so `/ranked` and `/ties` still list the probes and each
+    /// candidate keeps its score against them.
+    pub probe_threshold: usize,
+    /// Never suggest a word that was already guessed this game.
+    pub no_repeat_guesses: bool,
+    /// Never suggest a word with a letter found green anywhere but where it
+    /// was green, for variants that forbid moving a placed letter.
+    pub no_moved_greens: bool,
+    /// Only probe with words whose letters are distinct and untested, not
+    /// counting a letter where it's known to be green, to explore as much
+    /// of the alphabet as possible. Once no such word is left, the best
+    /// probe is used anyway. Guesses among the candidates in the endgame
+    /// aren't held to this.
+    pub fresh_letter_probes: bool,
+    /// How the frequency strategy scores guesses: "presence", "positional"
+    /// or "combined".
+    pub score_mode: ScoreMode,
+    /// When set, the frequency strategy picks the first guess of a game by
+    /// the combined score with a letter in its frequent position counting
+    /// this many times, rather than by `score_mode`, to go for greens on
+    /// the first turn. The combined score counts it 4 times.
+    pub opener_positional_weight: Option<u32>,
+    /// Reject feedback that no dictionary word would give for its guess,
+    /// instead of silently running out of candidates.
+    pub strict: bool,
+    /// The entropy, minimax and twomove strategies only evaluate this many
+    /// guesses, the best by frequency score, each turn.
+    pub candidate_cap: usize,
+    /// How long the entropy, minimax and twomove strategies may spend on a
+    /// turn. If the capped guesses aren't all evaluated in time, that turn
+    /// falls back to the frequency score, so the cap bounds the usual cost
+    /// and this only catches turns with unusually many candidates.
+    pub strategy_timeout_ms: u64,
+    /// Record what each hint did for `/trace`. This ranks the guesses an
+    /// extra time per hint.
+    pub trace: bool,
+    /// When the best opening guess repeats a letter, open with the best
+    /// guess of distinct letters instead.
+    pub prefer_distinct_opener: bool,
+    /// Only the best N words by frequency score against all the answers are
+    /// used as guesses, picked once at startup. Each turn then scores N words
+    /// instead of the whole dictionary, at the cost of sometimes missing a
+    /// better probe late in the game, when the good probes depend on the
+    /// candidates left. The candidates are added back at every reset, so
+    /// they can always be guessed. Unset by default,
+    /// which keeps every word; a few thousand is a reasonable N for
+    /// words_alpha.txt.
+    pub probe_pool: Option<usize>,
+    /// Which words guesses are drawn from, before `probe_pool` picks among
+    /// them. "all", the default, uses the whole dictionary, which gives the
+    /// most informative probes. "answers" only guesses answers, so every
+    /// guess can win but fewer candidates are ruled out per turn. "allowed"
+    /// uses the guesses that aren't answers, as listed after the blank line
+    /// of a `combined` file; without a separate list that leaves only the
+    /// candidates. Unless this is "all" the candidates are added back at
+    /// every reset, so they can always be guessed.
+    pub probe_source: ProbeSource,
+    /// Suggestions at least this likely to be the answer are declared as the
+    /// answer. The default of 1 only declares the last candidate, which is
+    /// always declared whatever the threshold.
+    pub declare_threshold: f64,
+    /// Lowers the frequency score of a guess for each tile spent on a letter
+    /// known to be absent, by this fraction of the candidate count. 0, the
+    /// default, only gives those tiles no score.
+    pub dead_letter_penalty: f64,
+    /// How much of the frequency score comes from the letters of the
+    /// allowed guesses rather than the candidates, from 0 to 1. The
+    /// candidates are the answers still possible, so their letters are what
+    /// the score should reward, but a curated answer list can skew them and
+    /// a handful of candidates is a noisy sample; blending in some of the
+    /// allowed guesses' distribution steadies the score. 0, the default,
+    /// only counts the candidates.
+    pub allowed_frequency_blend: f64,
+    /// A file written by `lingo precompute-opener` with the first guess for
+    /// each letter. It's only used for the strategy and word lists it was
+    /// made with.
+    pub opener_cache: String,
+    /// Guesses scoring at most this far below the best are listed as ties by
+    /// `/ties`. Scores are whole numbers for the letter-counting strategies,
+    /// so the default of 1e-6 only catches exact ties there while allowing
+    /// for rounding in entropy.
+    pub tie_epsilon: f64,
+    /// How much the robust strategy weighs an answer that only one feedback
+    /// tile rules out, against 1 for an answer matching every tile.
+    pub robust_near_miss_weight: f64,
+    /// Listen on a Unix socket at this path instead of TCP port 8088. A
+    /// leftover socket nobody is listening on is removed at startup. The
+    /// socket is created with the process umask, and clients need write
+    /// permission on it, so limit access with the umask or the permissions
+    /// of its directory.
+    pub socket: Option<String>,
+    /// How many threads serve requests, one per core when unset. Requests
+    /// that use the game share one lock, so strategy work only ever keeps
+    /// one core busy however many workers there are; more workers only let
+    /// other requests wait alongside it. The per-turn cost of the entropy,
+    /// minimax and twomove strategies is bounded by `candidate_cap` and
+    /// `strategy_timeout_ms` instead. On a small shared instance, 1 or 2
+    /// workers with a `candidate_cap` of 100 and a `strategy_timeout_ms`
+    /// of 250 keep a turn to a fraction of a second of one core. An
+    /// `opener_cache` written by `lingo precompute-opener`, which ignores
+    /// the timeout, saves the first turn, usually the slowest, from running
+    /// the strategy at all.
+    pub workers: Option<usize>,
+    /// The most words `/remaining` returns per page, and the page size when
+    /// none is asked for.
+    pub remaining_limit: usize,
+    /// Guesses allowed in a game from `/play/new`.
+    pub play_attempts: usize,
+    /// A file of words and how common they are, one pair per line, which
+    /// weighs the chance of each candidate being the answer. It can be
+    /// reloaded with `/weights/reload`.
+    pub weights: Option<String>,
+    /// Time the solver lock and every request, and serve the timings at
+    /// `/diag`.
+    pub diag: bool,
+    /// Break codes instead of guessing words: the dictionary becomes every
+    /// string of 5 of these symbols, such as "123456" for a Mastermind
+    /// style game, and `dictionary` is ignored. The symbols are allowed in
+    /// guesses without adding them to `extra_chars`. Up to 10 symbols fit
+    /// the limit of 100000 codes.
+    pub codeword_symbols: Option<String>,
+    /// Add `best_probe`, the strategy's pick from every allowed guess, and
+    /// `best_bet`, the likeliest candidate, to suggestions, so players can
+    /// choose between information and a chance to win now.
+    pub split_suggestion: bool,
+    /// Allow uppercase letters in words and guesses and keep them distinct,
+    /// so "Paris" and "paris" are different words, for variants with
+    /// proper nouns. Off by default, where dictionary lines with uppercase
+    /// letters are skipped. The cased feedback format can't be used with
+    /// this on, since it spells every guess in lowercase.
+    pub case_sensitive: bool,
+    /// Lowers the frequency score of a guess containing one of these
+    /// letters by its penalty, a fraction of the candidate count like
+    /// `dead_letter_penalty`, so words stuffed with common letters around a
+    /// rare one stop winning. For example `{"q": 0.3, "z": 0.3, "x": 0.2,
+    /// "j": 0.3}`. This is a nudge rather than a filter: a penalized word
+    /// can still win, a letter the hints show is in the answer isn't
+    /// penalized, and nothing is penalized while `weights` are loaded.
+    pub rare_letter_penalties: BTreeMap<char, f64>,
+    /// Each `lingo hard-words` and `lingo benchmark-csv` run appends its
+    /// summary to this JSON lines file, which `lingo bench-history` and
+    /// `/benchmark-history` list.
+    pub benchmark_history: String,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            dictionary: String::from("words_alpha.txt"),
+            extra_chars: String::new(),
+            dictionaries: BTreeMap::new(),
+            length_dictionaries: BTreeMap::new(),
+            answer_limit: None,
+            answers: None,
+            combined: None,
+            openers: Vec::new(),
+            endgame_threshold: 2,
+            probe_threshold: 0,
+            no_repeat_guesses: false,
+            fresh_letter_probes: false,
+            no_moved_greens: false,
+            score_mode: ScoreMode::Combined,
+            opener_positional_weight: None,
+            strict: false,
+            candidate_cap: 500,
+            strategy_timeout_ms: 1000,
+            trace: false,
+            prefer_distinct_opener: false,
+            probe_pool: None,
+            probe_source: ProbeSource::All,
+            declare_threshold: 1.0,
+            dead_letter_penalty: 0.0,
+            allowed_frequency_blend: 0.0,
+            opener_cache: String::from("openers.json"),
+            tie_epsilon: 1e-6,
+            robust_near_miss_weight: 0.25,
+            socket: None,
+            workers: None,
+            remaining_limit: 100,
+            play_attempts: 5,
+            weights: None,
+            diag: false,
+            codeword_symbols: None,
+            split_suggestion: false,
+            case_sensitive: false,
+            rare_letter_penalties: BTreeMap::new(),
+            benchmark_history: String::from("benchmark_history.jsonl"),
+        }
+    }
+}
+
+impl Config {
+    pub fn charset(&self) -> Charset {
+        let symbols = self.codeword_symbols.as_deref().unwrap_or("");
+        Charset::new(&format!("{}{}", self.extra_chars, symbols)).case_sensitive(self.case_sensitive)
+    }
+
+    /// Checks the options whose values have to fall in a range. Options
+    /// checked against the word lists are left to `Solver::check_openers`.
+    pub fn validate(&self) -> Result<(), LingoError> {
+        let mut errors = Vec::new();
+
+        if !(0.0..=1.0).contains(&self.allowed_frequency_blend) {
+            errors.push(FieldError::new("allowed_frequency_blend", "must be between 0 and 1"));
+        }
+
+        check(errors)
+    }
+
+    /// Reads the file named by `LINGO_CONFIG`, falling back to `lingo.json`,
+    /// and uses the defaults when neither exists.
+    pub fn load() -> Config {
+        let path = env::var("LINGO_CONFIG").unwrap_or_else(|_| String::from(CONFIG_PATH));
+
+        match File::open(&path) {
+            Ok(file) => serde_json::from_reader(file).expect("Error parsing config file"),
+            Err(_) => Config::default(),
+        }
+    }
+}
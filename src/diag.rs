@@ -0,0 +1,182 @@
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+// Lock and request timings for `/diag`. Nothing is recorded unless enabled,
+// so the only cost left is a flag check.
+#[derive(Debug, Default)]
+pub struct Diag {
+    enabled: bool,
+    // Callers blocked on the lock right now.
+    waiting: AtomicUsize,
+    stats: Mutex<DiagStats>,
+}
+
+#[derive(Debug, Default)]
+struct DiagStats {
+    locks: u64,
+    wait: Duration,
+    hold: Duration,
+    max_waiting: usize,
+    endpoints: BTreeMap<String, EndpointStats>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    requests: u64,
+    total: Duration,
+    max: Duration,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DiagReport {
+    pub locks: u64,
+    pub average_wait_ms: f64,
+    pub average_hold_ms: f64,
+    pub waiting: usize,
+    pub max_waiting: usize,
+    pub endpoints: BTreeMap<String, EndpointReport>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EndpointReport {
+    pub requests: u64,
+    pub average_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Diag {
+    pub fn new(enabled: bool) -> Diag {
+        Diag {
+            enabled,
+            ..Diag::default()
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn record_request(&self, endpoint: &str, elapsed: Duration) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut stats = self.stats.lock().expect("Error locking mutex");
+        let endpoint = stats.endpoints.entry(endpoint.to_string()).or_default();
+        endpoint.requests += 1;
+        endpoint.total += elapsed;
+        endpoint.max = endpoint.max.max(elapsed);
+    }
+
+    pub fn report(&self) -> DiagReport {
+        let stats = self.stats.lock().expect("Error locking mutex");
+
+        DiagReport {
+            locks: stats.locks,
+            average_wait_ms: average_ms(stats.wait, stats.locks),
+            average_hold_ms: average_ms(stats.hold, stats.locks),
+            waiting: self.waiting.load(Ordering::Relaxed),
+            max_waiting: stats.max_waiting,
+            endpoints: stats.endpoints.iter()
+                .map(|(name, endpoint)| (name.clone(), EndpointReport {
+                    requests: endpoint.requests,
+                    average_ms: average_ms(endpoint.total, endpoint.requests),
+                    max_ms: endpoint.max.as_secs_f64() * 1000.0,
+                }))
+                .collect(),
+        }
+    }
+}
+
+fn average_ms(total: Duration, count: u64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+
+    total.as_secs_f64() * 1000.0 / count as f64
+}
+
+// A mutex that reports how long callers wait for it and hold it to a
+// `Diag`.
+#[derive(Debug)]
+pub struct TimedMutex<T> {
+    mutex: Mutex<T>,
+    diag: Arc<Diag>,
+}
+
+pub struct TimedGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    diag: &'a Diag,
+    acquired: Instant,
+}
+
+impl<T> TimedMutex<T> {
+    pub fn new(value: T, diag: Arc<Diag>) -> TimedMutex<T> {
+        TimedMutex {
+            mutex: Mutex::new(value),
+            diag,
+        }
+    }
+
+    pub fn diag(&self) -> &Diag {
+        &self.diag
+    }
+
+    // Like `Mutex::lock`, without the guard in a poison error.
+    pub fn lock(&self) -> Result<TimedGuard<'_, T>, PoisonError<()>> {
+        if !self.diag.enabled {
+            let guard = self.mutex.lock().map_err(|_| PoisonError::new(()))?;
+            return Ok(TimedGuard {
+                guard,
+                diag: &self.diag,
+                acquired: Instant::now(),
+            });
+        }
+
+        let waiting = self.diag.waiting.fetch_add(1, Ordering::Relaxed) + 1;
+        let start = Instant::now();
+        let guard = self.mutex.lock();
+        self.diag.waiting.fetch_sub(1, Ordering::Relaxed);
+        let guard = guard.map_err(|_| PoisonError::new(()))?;
+        let acquired = Instant::now();
+
+        let mut stats = self.diag.stats.lock().expect("Error locking mutex");
+        stats.locks += 1;
+        stats.wait += acquired - start;
+        stats.max_waiting = stats.max_waiting.max(waiting);
+
+        Ok(TimedGuard {
+            guard,
+            diag: &self.diag,
+            acquired,
+        })
+    }
+}
+
+impl<T> Deref for TimedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for TimedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for TimedGuard<'_, T> {
+    fn drop(&mut self) {
+        if self.diag.enabled {
+            let held = self.acquired.elapsed();
+            self.diag.stats.lock().expect("Error locking mutex").hold += held;
+        }
+    }
+}
@@ -0,0 +1,785 @@
+use crate::clue::{Clue, Hint};
+use crate::error::{FieldError, LingoError};
+use crate::strategy::{GuessStrategy, ScoreMode};
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::thread;
+
+pub const WORD_LEN: usize = 5;
+// How much more a letter counts in the combined score when it is in its
+// frequent position.
+pub const POSITIONAL_WEIGHT: u32 = 4;
+// The most codes `Dictionary::codewords` builds, since every guess is scored
+// against every candidate.
+pub const MAX_CODEWORDS: usize = 100_000;
+pub type CharFrequency = HashMap<char, Vec<u32>>;
+pub type LetterPresence = HashMap<char, u32>;
+pub type PositionLetters = Vec<HashSet<char>>;
+
+#[cfg(feature = "embedded-dictionary")]
+pub const EMBEDDED_DICTIONARY: &str = include_str!("../words_alpha.txt");
+
+// The characters a word may contain: lowercase ASCII letters plus any extras
+// a variant allows, such as apostrophes or hyphens. A case-sensitive charset
+// allows uppercase letters too, and they stay distinct from lowercase ones.
+#[derive(Clone, Debug, Default)]
+pub struct Charset {
+    pub extra: Vec<char>,
+    pub case_sensitive: bool,
+}
+
+impl Charset {
+    pub fn new(extra: &str) -> Charset {
+        Charset {
+            extra: extra.chars().collect(),
+            case_sensitive: false,
+        }
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Charset {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    pub fn allows(&self, c: char) -> bool {
+        c.is_ascii_lowercase() || (self.case_sensitive && c.is_ascii_uppercase()) || self.extra.contains(&c)
+    }
+
+    pub fn describe(&self) -> String {
+        let letters = if self.case_sensitive { "letters" } else { "lowercase letters" };
+        if self.extra.is_empty() {
+            String::from(letters)
+        }
+        else {
+            format!("{} and {}", letters, self.extra.iter().collect::<String>())
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Word {
+    pub word: String,
+    pub index: usize,
+}
+
+impl Word {
+    // A word of `word_len` lowercase letters. The index starts at 0 and is set
+    // by the dictionary the word is added to.
+    pub fn new(s: impl Into<String>, word_len: usize) -> Result<Word, LingoError> {
+        Word::with_charset(s, word_len, &Charset::default())
+    }
+
+    pub fn with_charset(s: impl Into<String>, word_len: usize, charset: &Charset) -> Result<Word, LingoError> {
+        let word = s.into();
+
+        if word.chars().count() != word_len {
+            return Err(LingoError::Validation(vec![
+                FieldError::new("word", format!("must be {} letters long", word_len)),
+            ]));
+        }
+        if !word.chars().all(|c| charset.allows(c)) {
+            return Err(LingoError::Validation(vec![
+                FieldError::new("word", format!("must only contain {}", charset.describe())),
+            ]));
+        }
+
+        Ok(Word {
+            word,
+            index: 0,
+        })
+    }
+
+    pub fn distinct_letters(&self) -> bool {
+        let letters: HashSet<char> = self.word.chars().collect();
+        letters.len() == self.word.chars().count()
+    }
+
+    pub fn has(&self, clue: &Clue) -> bool {
+        let mut occur = 0;
+        for (idx, c) in self.word.chars().enumerate() {
+            if c == clue.c {
+                occur += 1;
+            }
+            let allowed = match clue.hints[idx] {
+                Hint::Yes => c == clue.c,
+                Hint::No => c != clue.c,
+                // Maybe places no restriction on this position; whether the
+                // letter has to appear somewhere is left to `clue.occur`.
+                Hint::Maybe => true,
+                // `Clue::from_input` never leaves a hint unset.
+                Hint::Unset => true,
+            };
+            if !allowed {
+                return false;
+            }
+        }
+        if occur < clue.occur {
+            return false;
+        }
+        if clue.max.is_some_and(|max| occur > max) {
+            return false;
+        }
+        true
+    }
+
+    // How many tiles of the word are letters from `dead`.
+    pub fn dead_tiles(&self, dead: &[char]) -> usize {
+        self.word.chars().filter(|c| dead.contains(c)).count()
+    }
+
+    pub fn score(&self, freq: &CharFrequency, mode: ScoreMode) -> u32 {
+        match mode {
+            ScoreMode::Presence => self.presence_score(freq),
+            ScoreMode::Positional => self.positional_score(freq),
+            ScoreMode::Combined => self.weighted_score(freq, POSITIONAL_WEIGHT),
+        }
+    }
+
+    // The combined score, with a letter in its frequent position counting
+    // `positional_weight` times.
+    pub fn weighted_score(&self, freq: &CharFrequency, positional_weight: u32) -> u32 {
+        self.presence_score(freq) + self.positional_score(freq) * positional_weight.saturating_sub(1)
+    }
+
+    // How often each distinct letter of the word appears anywhere.
+    fn presence_score(&self, freq: &CharFrequency) -> u32 {
+        let mut chars: Vec<char> = self.word.chars().collect();
+        chars.sort();
+        chars.dedup();
+
+        let mut score = 0;
+
+        for c in chars {
+            if let Some(f) = freq.get(&c) {
+                score += f.iter().sum::<u32>();
+            }
+        }
+
+        score
+    }
+
+    // How often each letter of the word appears at the position it is in.
+    fn positional_score(&self, freq: &CharFrequency) -> u32 {
+        let mut score = 0;
+
+        for (idx, c) in self.word.chars().enumerate() {
+            if let Some(f) = freq.get(&c) {
+                score += f[idx];
+            }
+        }
+
+        score
+    }
+
+    // Letters contained by about half of the remaining candidates split the
+    // set best, so each letter is weighted by n * (total - n).
+    pub fn rarity_score(&self, presence: &LetterPresence, total: u32) -> u64 {
+        let mut chars: Vec<char> = self.word.chars().collect();
+        chars.sort();
+        chars.dedup();
+
+        let mut score = 0;
+
+        for c in chars {
+            if let Some(&n) = presence.get(&c) {
+                score += n as u64 * (total - n) as u64;
+            }
+        }
+
+        score
+    }
+
+    // Each distinguishing letter the word tests counts twice, plus once more
+    // for every one placed where the candidates disagree on it.
+    pub fn distinguish_score(&self, letters: &PositionLetters) -> u32 {
+        let all: HashSet<char> = letters.iter().flatten().cloned().collect();
+
+        let mut chars: Vec<char> = self.word.chars().collect();
+        chars.sort();
+        chars.dedup();
+
+        let mut score = 2 * chars.iter().filter(|c| all.contains(c)).count() as u32;
+
+        for (idx, c) in self.word.chars().enumerate() {
+            if letters[idx].contains(&c) {
+                score += 1;
+            }
+        }
+
+        score
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Dictionary {
+    pub words: Vec<Word>,
+    pub ignore_letters: Vec<char>,
+    // Lines of the right length that were skipped for invalid characters.
+    pub rejected: usize,
+    pub charset: Charset,
+}
+
+impl Dictionary {
+    pub fn filter(&mut self, clue: &Clue) {
+        let certain = clue.max == Some(0) || !clue.hints.contains(&Hint::Maybe);
+
+        if certain {
+            self.ignore_letters.push(clue.c);
+        }
+
+        self.retain_matching(|w| w.has(clue));
+    }
+
+    // Keeps only the words matching `pred`. Every filter goes through here,
+    // so anything derived from the word list only has to be handled once.
+    // Cutting a list down to its best words, as `top` and the probe pool
+    // and candidate caps do, truncates it directly instead.
+    pub fn retain_matching(&mut self, pred: impl Fn(&Word) -> bool) {
+        self.words.retain(pred);
+    }
+
+    // The best word by `score`, or None for an empty dictionary.
+    pub fn sort(&mut self, freq: &CharFrequency, mode: ScoreMode) -> Option<Word> {
+        self.rank_by(|w| w.score(freq, mode) as f64);
+        self.words.first().cloned()
+    }
+
+    // Sorts the words best first by `score`, breaking ties by dictionary
+    // order, and returns the scores in the new order.
+    pub fn rank_by(&mut self, score: impl Fn(&Word) -> f64) -> Vec<f64> {
+        let mut scored: Vec<(f64, Word)> = self.words.drain(..)
+            .map(|w| (score(&w), w))
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0)
+            .unwrap_or(Ordering::Equal)
+            .then(a.1.index.cmp(&b.1.index)));
+
+        let scores = scored.iter().map(|s| s.0).collect();
+        self.words = scored.into_iter().map(|s| s.1).collect();
+        scores
+    }
+
+    // Ranks by a strategy's scores. This is monomorphized for concrete
+    // strategies and only dispatches dynamically for a `dyn GuessStrategy`.
+    pub fn rank_with<S: GuessStrategy + ?Sized>(&mut self, strategy: &S) -> Vec<f64> {
+        self.rank_by(|w| strategy.score(w))
+    }
+
+    // Like `rank_by`, but only reorders the first `n` words and returns
+    // their scores, leaving the rest where they are.
+    pub fn rank_head_by(&mut self, n: usize, score: impl Fn(&Word) -> f64) -> Vec<f64> {
+        let rest = self.words.split_off(n.min(self.words.len()));
+        let scores = self.rank_by(score);
+        self.words.extend(rest);
+        scores
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.iter().any(|w| w.word == word)
+    }
+
+    pub fn from_file(mut file: File, charset: &Charset) -> Dictionary {
+        let mut data = String::new();
+        file.read_to_string(&mut data)
+            .expect("Error reading dictionary file.");
+
+        Dictionary::from_text(&data, charset)
+    }
+
+    pub fn load(path: &str, charset: &Charset) -> io::Result<Dictionary> {
+        fs::read_to_string(path).map(|data| Dictionary::from_text(&data, charset))
+    }
+
+    // Words of other lengths are skipped silently, since dictionaries
+    // usually hold every length.
+    pub fn from_text(data: &str, charset: &Charset) -> Dictionary {
+        Dictionary::from_text_len(data, charset, WORD_LEN)
+    }
+
+    // Like `load`, keeping the words of `word_len` letters.
+    pub fn load_len(path: &str, charset: &Charset, word_len: usize) -> io::Result<Dictionary> {
+        fs::read_to_string(path).map(|data| Dictionary::from_text_len(&data, charset, word_len))
+    }
+
+    // Like `from_text`, keeping the words of `word_len` letters.
+    pub fn from_text_len(data: &str, charset: &Charset, word_len: usize) -> Dictionary {
+        let mut words = Vec::new();
+        let mut rejected = 0;
+
+        for line in data.lines() {
+            let line = line.trim();
+            if line.chars().count() != word_len {
+                continue;
+            }
+
+            match Word::with_charset(line, word_len, charset) {
+                Ok(mut word) => {
+                    word.index = words.len();
+                    words.push(word);
+                },
+                Err(_) => rejected += 1,
+            }
+        }
+
+        Dictionary {
+            words,
+            ignore_letters: Vec::new(),
+            rejected,
+            charset: charset.clone(),
+        }
+    }
+
+    // Every code of WORD_LEN symbols from `symbols`, repeats allowed, in
+    // order of the symbols given. Fails when that's more than
+    // MAX_CODEWORDS codes.
+    pub fn codewords(symbols: &str, charset: &Charset) -> Result<Dictionary, String> {
+        let mut unique: Vec<char> = Vec::new();
+        for c in symbols.chars() {
+            if !charset.allows(c) {
+                return Err(format!("symbol {:?} isn't allowed, add it to extra_chars", c));
+            }
+            if !unique.contains(&c) {
+                unique.push(c);
+            }
+        }
+
+        let count = unique.len().checked_pow(WORD_LEN as u32).filter(|&n| n <= MAX_CODEWORDS);
+        let count = match count {
+            Some(0) => return Err(String::from("no symbols given")),
+            Some(count) => count,
+            None => return Err(format!("{} symbols make more than {} codes", unique.len(), MAX_CODEWORDS)),
+        };
+
+        let words = (0..count)
+            .map(|index| {
+                // The index in base `unique.len()`, most significant digit first.
+                let mut digits = vec![unique[0]; WORD_LEN];
+                let mut rest = index;
+                for digit in digits.iter_mut().rev() {
+                    *digit = unique[rest % unique.len()];
+                    rest /= unique.len();
+                }
+                Word {
+                    word: digits.into_iter().collect(),
+                    index,
+                }
+            })
+            .collect();
+
+        Ok(Dictionary {
+            words,
+            ignore_letters: Vec::new(),
+            rejected: 0,
+            charset: charset.clone(),
+        })
+    }
+
+    // Reads a file of answers, a blank line, then the other allowed
+    // guesses, as published for some Wordle clones. Returns every word and
+    // the answers. Unlike `from_text`, a line of the wrong length is an
+    // error, since a combined file is meant for one word length.
+    pub fn load_combined(path: &str, charset: &Charset) -> io::Result<(Dictionary, Dictionary)> {
+        let data = fs::read_to_string(path)?;
+        Dictionary::from_combined_text(&data, charset).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn from_combined_text(data: &str, charset: &Charset) -> Result<(Dictionary, Dictionary), String> {
+        let mut words = Vec::new();
+        let mut seen = HashSet::new();
+        let mut answer_count = None;
+
+        for (idx, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                // Only the first blank line after some answers separates the
+                // sections; others are ignored.
+                if answer_count.is_none() && !words.is_empty() {
+                    answer_count = Some(words.len());
+                }
+                continue;
+            }
+
+            let mut word = Word::with_charset(line, WORD_LEN, charset)
+                .map_err(|err| format!("line {}: {}", idx + 1, err))?;
+            // Lists often repeat the answers among the guesses.
+            if !seen.insert(word.word.clone()) {
+                continue;
+            }
+            word.index = words.len();
+            words.push(word);
+        }
+
+        let answer_count = answer_count.ok_or("no blank line between the answers and the other guesses")?;
+        let dictionary = |words: Vec<Word>| Dictionary {
+            words,
+            ignore_letters: Vec::new(),
+            rejected: 0,
+            charset: charset.clone(),
+        };
+        let answers = words[..answer_count].to_vec();
+        Ok((dictionary(words), dictionary(answers)))
+    }
+
+    pub fn top(&self, n: usize) -> Dictionary {
+        let mut words = self.words.clone();
+        words.sort_by_key(|w| w.index);
+        words.truncate(n);
+
+        Dictionary {
+            words,
+            ignore_letters: self.ignore_letters.clone(),
+            rejected: self.rejected,
+            charset: self.charset.clone(),
+        }
+    }
+
+    pub fn empty() -> Dictionary {
+        Dictionary {
+            words: Vec::new(),
+            ignore_letters: Vec::new(),
+            rejected: 0,
+            charset: Charset::default(),
+        }
+    }
+
+    // How many words have each letter at each position, as a dense matrix
+    // with a row per letter from 'a' to 'z' and a column per position.
+    pub fn letter_matrix(&self) -> Vec<Vec<u32>> {
+        let freq = self.letter_counts();
+
+        (b'a'..=b'z')
+            .map(|c| freq.get(&(c as char)).cloned().unwrap_or_else(|| vec![0; WORD_LEN]))
+            .collect()
+    }
+
+    // `letter_counts` for scoring: letters known to be absent count for
+    // nothing, even where a candidate still has them.
+    pub fn char_frequency(&self) -> CharFrequency {
+        let mut freq = self.letter_counts();
+
+        for c in &self.ignore_letters {
+            if let Some(v) = freq.get_mut(c) {
+                *v = vec![0; WORD_LEN];
+            }
+        }
+
+        freq
+    }
+
+    // How many words have each letter at each position.
+    pub fn letter_counts(&self) -> CharFrequency {
+        let mut freq: CharFrequency = HashMap::new();
+
+        for word in &self.words {
+            for (idx, c) in word.word.chars().enumerate() {
+                match freq.get_mut(&c)  {
+                    Some(f) => f[idx] += 1,
+                    None => {
+                        let mut pos = vec![0; WORD_LEN];
+                        pos[idx] = 1;
+                        freq.insert(c, pos);
+                    },
+                }
+            }
+        }
+
+        freq
+    }
+
+    // Entropy in bits of the letter at each position across the words, the
+    // positions with the most uncertainty scoring highest.
+    pub fn position_entropy(&self) -> Vec<f64> {
+        let freq = self.char_frequency();
+
+        (0..WORD_LEN).map(|idx| {
+            let total: u32 = freq.values().map(|f| f[idx]).sum();
+
+            freq.values()
+                .filter(|f| f[idx] > 0)
+                .map(|f| {
+                    let p = f[idx] as f64 / total as f64;
+                    p * (1.0 / p).log2()
+                })
+                .sum()
+        }).collect()
+    }
+
+    // The words by their letter at `position`, alphabetically within each
+    // letter.
+    pub fn grouped_by(&self, position: usize) -> BTreeMap<char, Vec<String>> {
+        let mut groups: BTreeMap<char, Vec<String>> = BTreeMap::new();
+
+        for word in &self.words {
+            if let Some(c) = word.word.chars().nth(position) {
+                groups.entry(c).or_default().push(word.word.clone());
+            }
+        }
+        for words in groups.values_mut() {
+            words.sort();
+        }
+
+        groups
+    }
+
+    // The letter at each position when every word has the same one there.
+    // All None when there are no words.
+    pub fn determined_positions(&self) -> Vec<Option<char>> {
+        let freq = self.letter_counts();
+
+        (0..WORD_LEN).map(|idx| {
+            let mut letters = freq.iter().filter(|(_, f)| f[idx] > 0);
+            match (letters.next(), letters.next()) {
+                (Some((&c, _)), None) => Some(c),
+                _ => None,
+            }
+        }).collect()
+    }
+
+    // For each position, the share of the words with each letter there. The
+    // shares at a position sum to 1. With no words there are no letters to
+    // count, so every position is empty rather than dividing by zero.
+    pub fn position_probabilities(&self) -> Vec<BTreeMap<char, f64>> {
+        let freq = self.letter_counts();
+        let total = self.words.len();
+
+        (0..WORD_LEN).map(|idx| {
+            freq.iter()
+                .filter(|(_, f)| f[idx] > 0)
+                .map(|(&c, f)| (c, f[idx] as f64 / total as f64))
+                .collect()
+        }).collect()
+    }
+
+    // The letters at each position where the words don't all agree. A word
+    // with one of them at that position is told apart from the rest.
+    pub fn distinguishing_letters(&self) -> PositionLetters {
+        let mut letters = vec![HashSet::new(); WORD_LEN];
+
+        for word in &self.words {
+            for (idx, c) in word.word.chars().enumerate() {
+                letters[idx].insert(c);
+            }
+        }
+
+        for position in &mut letters {
+            if position.len() < 2 {
+                position.clear();
+            }
+        }
+
+        letters
+    }
+
+    // The two of the first `limit` words that differ in the fewest
+    // positions, and that number, taking the earliest pair on ties. None
+    // with fewer than two words.
+    pub fn closest_pair(&self, limit: usize) -> Option<(&Word, &Word, usize)> {
+        let words: Vec<Vec<char>> = self.words.iter().take(limit).map(|w| w.word.chars().collect()).collect();
+        let mut best: Option<(usize, usize, usize)> = None;
+
+        for (i, a) in words.iter().enumerate() {
+            for (j, b) in words.iter().enumerate().skip(i + 1) {
+                let distance = a.iter().zip(b).filter(|(x, y)| x != y).count();
+                if best.is_none_or(|(_, _, best_distance)| distance < best_distance) {
+                    best = Some((i, j, distance));
+                }
+            }
+            if best.is_some_and(|(_, _, distance)| distance == 1) {
+                break;
+            }
+        }
+
+        best.map(|(i, j, distance)| (&self.words[i], &self.words[j], distance))
+    }
+
+    pub fn letter_presence(&self) -> LetterPresence {
+        let mut presence: LetterPresence = HashMap::new();
+
+        for word in &self.words {
+            let mut chars: Vec<char> = word.word.chars().collect();
+            chars.sort();
+            chars.dedup();
+
+            for c in chars {
+                *presence.entry(c).or_insert(0) += 1;
+            }
+        }
+
+        for c in &self.ignore_letters {
+            presence.remove(c);
+        }
+
+        presence
+    }
+}
+
+// Reads a scoring profile, as served by `/profile`, so that words can be
+// scored offline the way they would be against those candidates.
+pub fn load_profile(path: &str) -> io::Result<CharFrequency> {
+    let file = File::open(path)?;
+    let profile: CharFrequency = serde_json::from_reader(file).map_err(io::Error::from)?;
+
+    if profile.values().any(|counts| counts.len() != WORD_LEN) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("every letter needs {} counts", WORD_LEN)));
+    }
+    Ok(profile)
+}
+
+// Scores a word against a profile, as `Word::score` would against the
+// candidates it was taken from.
+pub fn score_with_profile(word: &str, profile: &CharFrequency, mode: ScoreMode) -> Result<u32, LingoError> {
+    let charset = Charset::new(&profile.keys().collect::<String>());
+    Ok(Word::with_charset(word, WORD_LEN, &charset)?.score(profile, mode))
+}
+
+// Like `load_named`, for `config.length_dictionaries`: each file keeps the
+// words of the length it's listed under.
+pub fn load_lengths(paths: &BTreeMap<usize, String>, charset: &Charset) -> BTreeMap<usize, io::Result<Dictionary>> {
+    let handles: Vec<_> = paths.iter()
+        .map(|(&len, path)| {
+            let path = path.clone();
+            let charset = charset.clone();
+            (len, thread::spawn(move || Dictionary::load_len(&path, &charset, len)))
+        })
+        .collect();
+
+    handles.into_iter()
+        .map(|(len, handle)| {
+            let result = handle.join()
+                .unwrap_or_else(|_| Err(io::Error::other("loading thread panicked")));
+            (len, result)
+        })
+        .collect()
+}
+
+// Loads each named file on its own thread. A file that fails to load doesn't
+// stop the others.
+pub fn load_named(paths: &BTreeMap<String, String>, charset: &Charset) -> BTreeMap<String, io::Result<Dictionary>> {
+    let handles: Vec<_> = paths.iter()
+        .map(|(name, path)| {
+            let path = path.clone();
+            let charset = charset.clone();
+            (name.clone(), thread::spawn(move || Dictionary::load(&path, &charset)))
+        })
+        .collect();
+
+    handles.into_iter()
+        .map(|(name, handle)| {
+            let result = handle.join()
+                .unwrap_or_else(|_| Err(io::Error::other("loading thread panicked")));
+            (name, result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grade::grade;
+
+    // "shred" after `speed cnncc`: one 'e' is green, the other gray, so 'e'
+    // is ignored for scoring while every candidate still has it.
+    fn shred() -> Dictionary {
+        let mut words = Dictionary::from_text("shred\n", &Charset::default());
+        words.ignore_letters.push('e');
+        words
+    }
+
+    #[test]
+    fn letter_matrix_counts_ignored_letters() {
+        let matrix = shred().letter_matrix();
+
+        assert_eq!(matrix[(b'e' - b'a') as usize], vec![0, 0, 0, 1, 0]);
+        assert_eq!(matrix[(b's' - b'a') as usize], vec![1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn position_probabilities_count_ignored_letters() {
+        let probabilities = shred().position_probabilities();
+
+        assert_eq!(probabilities[3].get(&'e'), Some(&1.0));
+        for position in &probabilities {
+            assert!((position.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn determined_positions_include_ignored_letters() {
+        assert_eq!(shred().determined_positions(), vec![Some('s'), Some('h'), Some('r'), Some('e'), Some('d')]);
+    }
+
+    #[test]
+    fn apostrophe_words_need_the_extra_charset() {
+        let text = "don't\nwon't\nshred\n";
+
+        let plain = Dictionary::from_text(text, &Charset::default());
+        assert_eq!(plain.words.len(), 1);
+        assert_eq!(plain.rejected, 2);
+
+        let mut words = Dictionary::from_text(text, &Charset::new("'"));
+        assert_eq!(words.words.len(), 3);
+        for clue in Clue::from_input("don't", &grade("don't", "won't"), WORD_LEN) {
+            words.filter(&clue);
+        }
+        assert_eq!(words.words.iter().map(|w| w.word.as_str()).collect::<Vec<_>>(), vec!["won't"]);
+    }
+
+    #[test]
+    fn case_sensitive_words_stay_distinct() {
+        let text = "Paris\nparis\n";
+
+        let plain = Dictionary::from_text(text, &Charset::default());
+        assert_eq!(word_list(&plain), vec!["paris"]);
+
+        let mut words = Dictionary::from_text(text, &Charset::default().case_sensitive(true));
+        assert_eq!(word_list(&words), vec!["Paris", "paris"]);
+        let counts = words.letter_counts();
+        assert_eq!(counts[&'P'], vec![1, 0, 0, 0, 0]);
+        assert_eq!(counts[&'p'], vec![1, 0, 0, 0, 0]);
+
+        for clue in Clue::from_input("Paris", &grade("Paris", "paris"), WORD_LEN) {
+            words.filter(&clue);
+        }
+        assert_eq!(word_list(&words), vec!["paris"]);
+    }
+
+    #[test]
+    fn profile_round_trip_scores_like_the_candidates() {
+        let candidates = Dictionary::from_text("crane\ncrate\ncrave\nshred\n", &Charset::default());
+        let live = candidates.char_frequency();
+
+        let json = serde_json::to_string(&live).unwrap();
+        let profile: CharFrequency = serde_json::from_str(&json).unwrap();
+
+        let guess = Word::new("trace", WORD_LEN).unwrap();
+        for &mode in &[ScoreMode::Presence, ScoreMode::Positional, ScoreMode::Combined] {
+            assert_eq!(score_with_profile("trace", &profile, mode).unwrap(), guess.score(&live, mode));
+        }
+    }
+
+    fn word_list(dictionary: &Dictionary) -> Vec<&str> {
+        dictionary.words.iter().map(|w| w.word.as_str()).collect()
+    }
+
+    // Answers, a blank line, then the other guesses, repeating one answer.
+    const COMBINED: &str = "crane\ncrate\n\nslate\ncrane\n\ntrace\n";
+
+    #[test]
+    fn combined_file_splits_at_the_first_blank_line() {
+        let (words, answers) = Dictionary::from_combined_text(COMBINED, &Charset::default()).unwrap();
+
+        assert_eq!(word_list(&answers), vec!["crane", "crate"]);
+        assert_eq!(word_list(&words), vec!["crane", "crate", "slate", "trace"]);
+    }
+
+    #[test]
+    fn combined_file_needs_one_word_length() {
+        let err = Dictionary::from_combined_text("crane\n\ncrates\n", &Charset::default()).unwrap_err();
+        assert!(err.starts_with("line 3"));
+
+        assert!(Dictionary::from_combined_text("crane\ncrate\n", &Charset::default()).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use actix_web::{HttpResponse, ResponseError};
+use actix_web::http::StatusCode;
+use serde::Serialize;
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> FieldError {
+        FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LingoError {
+    Validation(Vec<FieldError>),
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    errors: &'a [FieldError],
+}
+
+impl fmt::Display for LingoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LingoError::Validation(errors) => {
+                let messages: Vec<String> = errors.iter()
+                    .map(|e| format!("{}: {}", e.field, e.message))
+                    .collect();
+                write!(f, "{}", messages.join(", "))
+            },
+        }
+    }
+}
+
+impl std::error::Error for LingoError {}
+
+impl ResponseError for LingoError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            LingoError::Validation(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            LingoError::Validation(errors) => HttpResponse::build(self.status_code())
+                .json(ErrorBody { errors }),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::dictionary::Dictionary;
+use crate::grade::grade;
+
+use serde::Serialize;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A game where the server knows the answer and grades the player's guesses.
+#[derive(Clone, Debug)]
+pub struct Game {
+    answer: String,
+    pub attempts: usize,
+    pub max_attempts: usize,
+    pub solved: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameStart {
+    pub first_letter: char,
+    pub length: usize,
+    pub max_attempts: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GuessResult {
+    pub guess: String,
+    pub feedback: String,
+    pub attempts: usize,
+    pub solved: bool,
+    pub finished: bool,
+    // Only revealed once the game is finished.
+    pub answer: Option<String>,
+}
+
+impl Game {
+    // Picks an answer from `answers`, seeded from the clock. None if there
+    // are no answers.
+    pub fn random(answers: &Dictionary, max_attempts: usize) -> Option<Game> {
+        if answers.words.is_empty() {
+            return None;
+        }
+
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as usize ^ d.as_secs() as usize)
+            .unwrap_or(0);
+        let answer = answers.words[nanos % answers.words.len()].word.clone();
+
+        Some(Game {
+            answer,
+            attempts: 0,
+            max_attempts,
+            solved: false,
+        })
+    }
+
+    pub fn start(&self) -> GameStart {
+        GameStart {
+            first_letter: self.answer.chars().next().unwrap_or(' '),
+            length: self.answer.chars().count(),
+            max_attempts: self.max_attempts,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.solved || self.attempts >= self.max_attempts
+    }
+
+    pub fn guess(&mut self, guess: &str) -> GuessResult {
+        let feedback = grade(guess, &self.answer);
+
+        self.attempts += 1;
+        self.solved = guess == self.answer;
+
+        GuessResult {
+            guess: guess.to_string(),
+            feedback,
+            attempts: self.attempts,
+            solved: self.solved,
+            finished: self.finished(),
+            answer: if self.finished() { Some(self.answer.clone()) } else { None },
+        }
+    }
+}
+
+// Results of every finished game since the server started. They're global
+// rather than per player, since the server only hosts one game at a time.
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub played: usize,
+    pub won: usize,
+    pub current_streak: usize,
+    pub best_streak: usize,
+    // Won games by the number of attempts they took, from 1.
+    pub distribution: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StatsReport {
+    pub played: usize,
+    pub won: usize,
+    pub win_rate: f64,
+    pub average_attempts: Option<f64>,
+    pub current_streak: usize,
+    pub best_streak: usize,
+    pub distribution: Vec<usize>,
+}
+
+impl Stats {
+    // Counts a finished or abandoned game. Abandoned games are losses.
+    pub fn record(&mut self, game: &Game) {
+        self.played += 1;
+
+        if game.solved {
+            self.won += 1;
+            self.current_streak += 1;
+            self.best_streak = self.best_streak.max(self.current_streak);
+
+            if self.distribution.len() < game.attempts {
+                self.distribution.resize(game.attempts, 0);
+            }
+            self.distribution[game.attempts - 1] += 1;
+        }
+        else {
+            self.current_streak = 0;
+        }
+    }
+
+    pub fn report(&self) -> StatsReport {
+        let attempts: usize = self.distribution.iter()
+            .enumerate()
+            .map(|(idx, count)| (idx + 1) * count)
+            .sum();
+
+        StatsReport {
+            played: self.played,
+            won: self.won,
+            win_rate: if self.played == 0 { 0.0 } else { self.won as f64 / self.played as f64 },
+            average_attempts: if self.won == 0 { None } else { Some(attempts as f64 / self.won as f64) },
+            current_streak: self.current_streak,
+            best_streak: self.best_streak,
+            distribution: self.distribution.clone(),
+        }
+    }
+}
+
+// The game being played, if any, and the stats of the ones before it.
+#[derive(Clone, Debug, Default)]
+pub struct Play {
+    pub game: Option<Game>,
+    pub stats: Stats,
+}
+
+impl Play {
+    // Starts a new game, counting an unfinished one as lost.
+    pub fn new_game(&mut self, answers: &Dictionary, max_attempts: usize) -> Option<GameStart> {
+        if let Some(game) = self.game.take() {
+            if !game.finished() {
+                self.stats.record(&game);
+            }
+        }
+
+        let game = Game::random(answers, max_attempts)?;
+        let start = game.start();
+        self.game = Some(game);
+        Some(start)
+    }
+
+    // Grades a guess in the current game, or None if there's no game going.
+    pub fn guess(&mut self, guess: &str) -> Option<GuessResult> {
+        let game = self.game.as_mut().filter(|g| !g.finished())?;
+        let result = game.guess(guess);
+
+        if result.finished {
+            let game = game.clone();
+            self.stats.record(&game);
+        }
+        Some(result)
+    }
+}
@@ -0,0 +1,181 @@
+use crate::dictionary::Word;
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+// The feedback the game shows for `guess` against `answer`. Greens are
+// marked first, then a letter is yellow only while the answer still has an
+// unmatched copy of it, so a repeated letter is never over-counted.
+pub fn grade(guess: &str, answer: &str) -> String {
+    let guess: Vec<char> = guess.chars().collect();
+    let answer: Vec<char> = answer.chars().collect();
+
+    let mut feedback = vec!['n'; guess.len()];
+    let mut unmatched: HashMap<char, u32> = HashMap::new();
+
+    for (idx, &c) in answer.iter().enumerate() {
+        if guess.get(idx) == Some(&c) {
+            feedback[idx] = 'c';
+        }
+        else {
+            *unmatched.entry(c).or_insert(0) += 1;
+        }
+    }
+
+    for (idx, c) in guess.iter().enumerate() {
+        if feedback[idx] == 'c' {
+            continue;
+        }
+        if let Some(count) = unmatched.get_mut(c) {
+            if *count > 0 {
+                *count -= 1;
+                feedback[idx] = 'w';
+            }
+        }
+    }
+
+    feedback.into_iter().collect()
+}
+
+// How many of `words` give each feedback for `guess`.
+pub fn partition(guess: &str, words: &[Word]) -> HashMap<String, usize> {
+    let mut partitions = HashMap::new();
+
+    for word in words {
+        *partitions.entry(grade(guess, &word.word)).or_insert(0) += 1;
+    }
+
+    partitions
+}
+
+// Expected information in bits from a guess producing `partitions` over
+// `total` candidates.
+pub fn entropy(partitions: &HashMap<String, usize>, total: usize) -> f64 {
+    partitions.values()
+        .map(|&n| {
+            let p = n as f64 / total as f64;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+// The most candidates that can be left after a guess producing `partitions`.
+pub fn worst_case(partitions: &HashMap<String, usize>) -> usize {
+    partitions.values().cloned().max().unwrap_or(0)
+}
+
+// The chance that a guess producing `partitions` over `total` equally likely
+// candidates leaves a single one, so the game is won with this guess or the
+// next. Ties go to the guess with more partitions, which gives the next
+// guess more to work with.
+pub fn two_move_chance(partitions: &HashMap<String, usize>, total: usize) -> f64 {
+    let singles = partitions.values().filter(|&&n| n == 1).count();
+    // Below 1, so it never outweighs another single.
+    let spread = partitions.len() as f64 / (total + 1) as f64;
+
+    (singles as f64 + spread) / total as f64
+}
+
+// How many candidates are expected to be left after a guess producing
+// `partitions` over `total` equally likely candidates.
+pub fn expected_remaining(partitions: &HashMap<String, usize>, total: usize) -> f64 {
+    partitions.values()
+        .map(|&n| (n * n) as f64)
+        .sum::<f64>() / total as f64
+}
+
+// Greedily picks guesses from `guesses` whose feedback together tells all
+// `candidates` apart, each time taking the one that splits the candidates
+// into the most groups, with ties going to the earlier guess, so a ranked
+// pool keeps its best words. This is approximate, as the smallest set is a
+// set cover problem, and stops after `max_guesses` guesses, when no guess
+// splits any group further, or once `timeout` has passed. Returns the
+// guesses and whether every candidate ended up alone.
+pub fn distinguishing_set(guesses: &[Word], candidates: &[Word], max_guesses: usize, timeout: Duration) -> (Vec<String>, bool) {
+    let start = Instant::now();
+    // The group of each candidate, numbered from 0.
+    let mut groups = vec![0; candidates.len()];
+    let mut group_count = usize::from(!candidates.is_empty());
+    let mut chosen = Vec::new();
+
+    let mut timed_out = false;
+    while !timed_out && group_count < candidates.len() && chosen.len() < max_guesses {
+        // Out of time, the best guess so far is still taken.
+        let mut best: Option<(&Word, usize)> = None;
+        for guess in guesses {
+            if start.elapsed() > timeout {
+                timed_out = true;
+                break;
+            }
+
+            let split: HashSet<(usize, String)> = groups.iter()
+                .zip(candidates)
+                .map(|(&group, word)| (group, grade(&guess.word, &word.word)))
+                .collect();
+            if best.is_none_or(|(_, count)| split.len() > count) {
+                best = Some((guess, split.len()));
+            }
+        }
+
+        let guess = match best {
+            Some((guess, count)) if count > group_count => guess,
+            _ => break,
+        };
+
+        let mut ids = HashMap::new();
+        for (group, word) in groups.iter_mut().zip(candidates) {
+            let next = ids.len();
+            *group = *ids.entry((*group, grade(&guess.word, &word.word))).or_insert(next);
+        }
+        group_count = ids.len();
+        chosen.push(guess.word.clone());
+    }
+
+    (chosen, group_count == candidates.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::WORD_LEN;
+
+    fn words(words: &[&str]) -> Vec<Word> {
+        words.iter().map(|w| Word::new(*w, WORD_LEN).unwrap()).collect()
+    }
+
+    #[test]
+    fn distinguishing_set_stops_at_timeout() {
+        let candidates = words(&["baker", "faker", "maker", "taker"]);
+        let guesses = words(&["fembt", "baker"]);
+
+        let (chosen, complete) = distinguishing_set(&guesses, &candidates, 8, Duration::from_secs(60));
+        assert_eq!(chosen, vec![String::from("fembt")]);
+        assert!(complete);
+
+        let (_, complete) = distinguishing_set(&guesses, &candidates, 8, Duration::from_secs(0));
+        assert!(!complete);
+    }
+
+    #[test]
+    fn distinguishing_set_ties_go_to_the_earlier_guess() {
+        let candidates = words(&["baker", "faker", "maker", "taker"]);
+
+        // Both tell all four apart.
+        let (chosen, _) = distinguishing_set(&words(&["fembt", "bfmzz"]), &candidates, 8, Duration::from_secs(60));
+        assert_eq!(chosen, vec![String::from("fembt")]);
+        let (chosen, _) = distinguishing_set(&words(&["bfmzz", "fembt"]), &candidates, 8, Duration::from_secs(60));
+        assert_eq!(chosen, vec![String::from("bfmzz")]);
+    }
+
+    #[test]
+    fn two_move_chance_prefers_singles_to_even_splits() {
+        let candidates = words(&["bakes", "baket", "fakel", "fakem", "makel", "makem"]);
+        // Leaves "bakes" or "baket" alone, or the other four together.
+        let singles = partition("bzzzs", &candidates);
+        // Tells the first letters apart, leaving pairs.
+        let pairs = partition("bfzzz", &candidates);
+
+        assert!(expected_remaining(&pairs, candidates.len()) < expected_remaining(&singles, candidates.len()));
+        assert!(two_move_chance(&singles, candidates.len()) > two_move_chance(&pairs, candidates.len()));
+    }
+}
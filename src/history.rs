@@ -0,0 +1,75 @@
+use crate::opener::fingerprint;
+use crate::simulate::{average_guesses, Outcome};
+use crate::solver::Solver;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The summary of one benchmark run, kept as a line of the benchmark history.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchmarkRun {
+    // When the run finished, in seconds since the Unix epoch.
+    pub time: u64,
+    pub strategy: String,
+    // `fingerprint` of the guesses and answers, in hex, so runs over
+    // different word lists can be told apart.
+    pub dictionary: String,
+    // The config the run was made with.
+    pub config: serde_json::Value,
+    pub answers: usize,
+    pub unsolved: usize,
+    // The mean guess count over solved games, None if none were.
+    pub average: Option<f64>,
+    // The most guesses a solved game took.
+    pub worst: Option<usize>,
+}
+
+impl BenchmarkRun {
+    pub fn new(solver: &Solver, outcomes: &[Outcome]) -> BenchmarkRun {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        BenchmarkRun {
+            time,
+            strategy: solver.strategy.name().to_string(),
+            dictionary: fingerprint(&solver.all_words, &solver.all_answers),
+            config: serde_json::to_value(&solver.config).unwrap_or(serde_json::Value::Null),
+            answers: outcomes.len(),
+            unsolved: outcomes.iter().filter(|o| o.guesses.is_none()).count(),
+            average: average_guesses(outcomes),
+            worst: outcomes.iter().filter_map(|o| o.guesses).max(),
+        }
+    }
+
+    // Adds the run to the end of the history at `path`, creating it if
+    // needed.
+    pub fn append(&self, path: &str) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let line = serde_json::to_string(self).map_err(io::Error::from)?;
+        writeln!(file, "{}", line)
+    }
+}
+
+// The runs in the history at `path`, oldest first. A missing file is an
+// empty history.
+pub fn load(path: &str) -> io::Result<Vec<BenchmarkRun>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut runs = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        runs.push(serde_json::from_str(&line).map_err(io::Error::from)?);
+    }
+    Ok(runs)
+}
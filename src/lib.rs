@@ -0,0 +1,17 @@
+pub mod clue;
+pub mod config;
+pub mod diag;
+pub mod dictionary;
+pub mod error;
+pub mod game;
+pub mod grade;
+pub mod history;
+pub mod metrics;
+pub mod opener;
+pub mod simulate;
+pub mod snapshot;
+pub mod solver;
+pub mod strategy;
+pub mod validate;
+pub mod weights;
+pub mod ws;
@@ -1,334 +1,1244 @@
-use actix_web::{web, App, HttpServer, Responder};
+use actix_web::dev::Service;
+use actix_web::http::StatusCode;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use serde::{Deserialize, Serialize};
 
-use std::collections::HashMap;
+use lingo::clue::{decode_cased, ConstraintSet, FirstLetter};
+use lingo::config::Config;
+use lingo::diag::{Diag, TimedMutex};
+use lingo::dictionary::{self, Dictionary, WORD_LEN};
+use lingo::error::{FieldError, LingoError};
+use lingo::game::Play;
+use lingo::grade::grade;
+use lingo::history::{self, BenchmarkRun};
+use lingo::opener::{self, OpenerCache};
+use lingo::simulate;
+use lingo::snapshot::Snapshot;
+use lingo::solver::{GameSettings, Solver, Suggestion};
+use lingo::strategy::Strategy;
+use lingo::validate::{check, validate_feedback, validate_guess, validate_lengths, validate_spelling};
+use lingo::weights::Weights;
+use lingo::ws::Session;
+
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
-use std::io::Read;
-use std::sync::Mutex;
+use std::io::{BufWriter, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-const WORD_LEN: usize = 5;
-type CharFrequency = HashMap<char, Vec<u32>>;
-type AppState = Mutex<State>;
+const DEFAULT_RANKED_LIMIT: usize = 10;
+const DEFAULT_HARD_WORDS: usize = 100;
+const MAX_RESET_BATCH: usize = 100;
+const DEFAULT_HARD_WORDS_PATH: &str = "hard_words.txt";
+const DEFAULT_BENCHMARK_CSV_PATH: &str = "benchmark.csv";
+const DEFAULT_FUZZ_ROUNDS: usize = 10_000;
+type AppState = TimedMutex<Solver>;
+type Dictionaries = BTreeMap<String, Dictionary>;
+type LengthDictionaries = BTreeMap<usize, Dictionary>;
+type PlayState = Mutex<Play>;
 
-#[derive(Clone, Debug)]
-struct Word {
-    word: String,
+#[derive(Deserialize)]
+struct HintRequest {
+    guess: String,
+    feedback: String,
 }
 
-impl Word {
-    fn has(&self, clue: &Clue) -> bool {
-        let mut occur = 0;
-        for (idx, c) in self.word.chars().enumerate() {
-            if c == clue.c {
-                occur += 1;
-            }
-            match clue.hints[idx] {
-                Hint::Yes => {
-                    if c != clue.c  {
-                        return false;
-                    }
-                },
-                Hint::No => {
-                    if c == clue.c {
-                        return false;
-                    }
-                },
-                _ => {},
+// A whole game, for suggestions that don't touch the server's game.
+#[derive(Deserialize)]
+struct SuggestRequest {
+    letter: char,
+    mode: Option<FirstLetter>,
+    strategy: Option<String>,
+    history: Vec<HintRequest>,
+}
+
+impl SuggestRequest {
+    fn validate(&self, dictionary: &Dictionary) -> Result<(), LingoError> {
+        let mut errors = Vec::new();
+
+        if let Some(name) = &self.strategy {
+            if Strategy::from_name(name).is_none() {
+                errors.push(FieldError::new("strategy", format!("unknown strategy {}", name)));
             }
         }
-        if occur < clue.occur {
-            return false;
+        for (idx, turn) in self.history.iter().enumerate() {
+            validate_guess(&format!("history[{}].guess", idx), &turn.guess, dictionary, &mut errors);
+            validate_feedback(&format!("history[{}].feedback", idx), &turn.feedback, &mut errors);
         }
-        true
+
+        check(errors)
     }
+}
 
-    fn score(&self, freq: &CharFrequency) -> u32 {
-        let mut chars: Vec<char> = self.word.chars().collect();
-        chars.sort();
-        chars.dedup();
+// An extra fact about the answer, like "at least two e's", that no
+// feedback would give.
+#[derive(Deserialize)]
+struct OccurrenceRequest {
+    letter: char,
+    min: Option<u32>,
+    max: Option<u32>,
+}
 
-        let mut score = 0;
+fn validate_occurrences(occurrences: &[OccurrenceRequest], dictionary: &Dictionary) -> Result<(), LingoError> {
+    let mut errors = Vec::new();
 
-        for c in chars {
-            if let Some(f) = freq.get(&c) {
-                for (idx, count) in f.iter().enumerate() {
-                    if self.word.chars().nth(idx).unwrap() == c {
-                        score += count * 4;
-                    }
-                    else {
-                        score += count;
-                    }
-                }
+    for (idx, occurrence) in occurrences.iter().enumerate() {
+        if !dictionary.charset.allows(occurrence.letter) {
+            errors.push(FieldError::new(&format!("[{}].letter", idx), format!("must be one of {}", dictionary.charset.describe())));
+        }
+        let min = occurrence.min.unwrap_or(0);
+        if min > WORD_LEN as u32 {
+            errors.push(FieldError::new(&format!("[{}].min", idx), format!("must be at most {}", WORD_LEN)));
+        }
+        if let Some(max) = occurrence.max {
+            if max < min {
+                errors.push(FieldError::new(&format!("[{}].max", idx), "must be at least min"));
             }
         }
-
-        score
     }
+
+    check(errors)
 }
 
-#[derive(Clone, Debug)]
-struct Dictionary {
-    words: Vec<Word>,
-    ignore_letters: Vec<char>,
+#[derive(Deserialize)]
+struct BatchRequest {
+    guess: String,
+    feedbacks: Vec<String>,
 }
 
-impl Dictionary {
-    fn filter(&mut self, clue: &Clue) {
-        let mut certain = true;
-        for hint in &clue.hints {
-            if let Hint::Maybe = hint {
-                certain = false;
-                break;
-            }
+impl BatchRequest {
+    fn validate(&self, dictionary: &Dictionary) -> Result<(), LingoError> {
+        let mut errors = Vec::new();
+
+        validate_guess("guess", &self.guess, dictionary, &mut errors);
+        for (idx, feedback) in self.feedbacks.iter().enumerate() {
+            validate_feedback(&format!("feedbacks[{}]", idx), feedback, &mut errors);
         }
 
-        if certain {
-            self.ignore_letters.push(clue.c);
+        check(errors)
+    }
+}
+
+// `mode` is "green" (the default) for an answer starting with the letter,
+// "present" for an answer containing it anywhere, or "ignore" to start
+// from the whole dictionary.
+#[derive(Deserialize)]
+struct ResetQuery {
+    mode: Option<FirstLetter>,
+}
+
+// A reset's query: `mode` as in `ResetQuery`, and optionally the first
+// `guess` and its `feedback` if they were played elsewhere.
+#[derive(Deserialize)]
+struct StartQuery {
+    mode: Option<FirstLetter>,
+    guess: Option<String>,
+    feedback: Option<String>,
+}
+
+// `dictionary=false` grades words that aren't in the dictionary.
+#[derive(Deserialize)]
+struct GradeQuery {
+    dictionary: Option<bool>,
+}
+
+// `all=true` lists runs over every dictionary, not just the current one.
+#[derive(Deserialize)]
+struct HistoryQuery {
+    all: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct RankedQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct ResetBatchRequest {
+    resets: Vec<ResetItem>,
+}
+
+#[derive(Deserialize)]
+struct ResetItem {
+    letter: char,
+    mode: Option<FirstLetter>,
+}
+
+impl ResetBatchRequest {
+    fn validate(&self) -> Result<(), LingoError> {
+        let mut errors = Vec::new();
+
+        if self.resets.len() > MAX_RESET_BATCH {
+            errors.push(FieldError::new("resets", format!("must have at most {} resets", MAX_RESET_BATCH)));
         }
 
-        self.words = self.words.clone().into_iter()
-            .filter(|w| w.has(clue))
-            .collect();
+        check(errors)
     }
+}
+
+#[derive(Serialize)]
+struct ResetSuggestion {
+    letter: char,
+    mode: &'static str,
+    #[serde(flatten)]
+    suggestion: Suggestion,
+}
+
+#[derive(Serialize)]
+struct BatchSuggestion {
+    feedback: String,
+    #[serde(flatten)]
+    suggestion: Suggestion,
+}
+
+async fn reset(path: web::Path<(char,)>, query: web::Query<StartQuery>, state: web::Data<AppState>) -> Result<String, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    // Checked before the reset, so a bad first turn leaves the game alone.
+    let first = match (&query.guess, &query.feedback) {
+        (Some(guess), Some(feedback)) => {
+            let mut errors = Vec::new();
+            validate_guess("guess", guess, &state.all_words, &mut errors);
+            validate_feedback("feedback", feedback, &mut errors);
+            check(errors)?;
+            state.check_feedback(guess, feedback)?;
+            Some((guess, feedback))
+        },
+        (None, None) => None,
+        (Some(_), None) => return Err(LingoError::Validation(vec![FieldError::new("feedback", "is required with a guess")])),
+        (None, Some(_)) => return Err(LingoError::Validation(vec![FieldError::new("guess", "is required with feedback")])),
+    };
 
-    fn sort(&mut self, freq: &CharFrequency) -> Word {
-        self.words.sort_by_cached_key(|w| -(w.score(freq) as i64));
-        self.words[0].clone()
+    state.reset_with(path.0, query.mode.unwrap_or(FirstLetter::Green));
+    if let Some((guess, feedback)) = first {
+        state.hint(guess, feedback);
     }
 
-    fn from_file(mut file: File) -> Dictionary {
-        let mut data = String::new();
-        file.read_to_string(&mut data)
-            .expect("Error reading dictionary file.");
+    Ok(state.get_guess())
+}
 
-        let mut words = Vec::new();
+// Just the next guess and a newline, as text/plain whatever the client
+// accepts. The body is empty once the game is solved or no words are left.
+async fn next_text(state: web::Data<AppState>) -> HttpResponse {
+    let mut state = state.lock().expect("Error locking mutex");
 
-        for line in data.lines() {
-            let word = line.trim().to_string();
+    let body = match state.next_guess() {
+        Some(guess) if !state.solved() => format!("{}\n", guess),
+        _ => String::new(),
+    };
+    HttpResponse::Ok().content_type("text/plain; charset=utf-8").body(body)
+}
 
-            if word.len() == WORD_LEN {
-                words.push(Word{word});
-            }
-        }
+async fn openers(path: web::Path<(char,)>, query: web::Query<ResetQuery>, state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
 
-        Dictionary {
-            words,
-            ignore_letters: Vec::new(),
-        }
+    HttpResponse::Ok().json(state.openers(path.0, query.mode.unwrap_or(FirstLetter::Green)))
+}
+
+// A reset whose body can override some of the server's settings for this
+// game only.
+async fn reset_with_settings(path: web::Path<(char,)>, query: web::Query<ResetQuery>, body: web::Json<GameSettings>, state: web::Data<AppState>) -> Result<String, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    body.validate()?;
+    state.reset_with_settings(path.0, query.mode.unwrap_or(FirstLetter::Green), &body);
+
+    Ok(state.get_guess())
+}
+
+async fn hint(path: web::Path<(String, String)>, state: web::Data<AppState>) -> Result<String, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    validate_lengths(&path.0, &path.1)?;
+    state.check_feedback(&path.0, &path.1)?;
+    state.hint(&path.0, &path.1);
+
+    Ok(state.get_guess())
+}
+
+// The feedback `guess` gets against `answer`, without touching the game.
+async fn grade_words(path: web::Path<(String, String)>, query: web::Query<GradeQuery>, state: web::Data<AppState>) -> Result<String, LingoError> {
+    let state = state.lock().expect("Error locking mutex");
+
+    let mut errors = Vec::new();
+    if query.dictionary.unwrap_or(true) {
+        validate_guess("guess", &path.0, &state.all_words, &mut errors);
+        validate_guess("answer", &path.1, &state.all_words, &mut errors);
+    }
+    else {
+        validate_spelling("guess", &path.0, &state.all_words.charset, &mut errors);
+        validate_spelling("answer", &path.1, &state.all_words.charset, &mut errors);
     }
+    check(errors)?;
 
-    fn empty() -> Dictionary {
-        Dictionary {
-            words: Vec::new(),
-            ignore_letters: Vec::new(),
-        }
+    Ok(grade(&path.0, &path.1))
+}
+
+async fn hint_cased(path: web::Path<(String,)>, state: web::Data<AppState>) -> Result<String, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    // Casing carries the feedback, so it can't also tell letters apart.
+    if state.all_words.charset.case_sensitive {
+        return Err(LingoError::Validation(vec![
+            FieldError::new("input", "cased feedback can't be used with a case-sensitive dictionary"),
+        ]));
     }
+    let (guess, feedback) = decode_cased(&path.0, WORD_LEN)?;
+    let mut errors = Vec::new();
+    validate_guess("guess", &guess, &state.all_words, &mut errors);
+    check(errors)?;
+    state.check_feedback(&guess, &feedback)?;
 
-    fn char_frequency(&self) -> CharFrequency {
-        let mut freq: CharFrequency = HashMap::new();
+    state.hint(&guess, &feedback);
 
-        for word in &self.words {
-            for (idx, c) in word.word.char_indices() {
-                match freq.get_mut(&c)  {
-                    Some(f) => f[idx] += 1,
-                    None => {
-                        let mut pos = vec![0; WORD_LEN];
-                        pos[idx] = 1;
-                        freq.insert(c, pos);
-                    },
-                }
-            }
-        }
+    Ok(state.get_guess())
+}
 
-        for c in &self.ignore_letters {
-            if let Some(v) = freq.get_mut(c) {
-                *v = vec![0; WORD_LEN];
-            }
-        }
+async fn post_hint(body: web::Json<HintRequest>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    let result = state.turn(&body.guess, &body.feedback)?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// Replays the posted game on a fresh solver, so any server can answer
+// without keeping a session. Every request pays for the reset and all of
+// its hints again, where the stateful routes only pay for the newest hint.
+async fn suggest(body: web::Json<SuggestRequest>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let mut solver = {
+        let state = state.lock().expect("Error locking mutex");
+        body.validate(&state.all_words)?;
+        state.fresh()
+    };
 
-        freq
+    if let Some(strategy) = body.strategy.as_deref().and_then(Strategy::from_name) {
+        solver.strategy = strategy;
     }
+    let hints: Vec<(String, String)> = body.history.iter()
+        .map(|turn| (turn.guess.clone(), turn.feedback.clone()))
+        .collect();
+    let mode = body.mode.unwrap_or(FirstLetter::Green);
+    solver.validate_history(Some((body.letter, mode)), "history", &hints)?;
+
+    solver.reset_with(body.letter, mode);
+    for (guess, feedback) in &hints {
+        solver.hint(guess, feedback);
+    }
+
+    Ok(HttpResponse::Ok().json(solver.suggestion()))
 }
 
-#[derive(Clone, Debug, PartialEq)]
-enum Hint {
-    Yes,
-    No,
-    Maybe,
-    Unset,
+// Like `suggest`, but seeded from a compact constraint string instead of the
+// game's hints.
+async fn suggest_compact(path: web::Path<(String,)>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let constraints = ConstraintSet::from_compact_string(&path.0, WORD_LEN)?;
+    let mut solver = state.lock().expect("Error locking mutex").fresh();
+
+    solver.seed(constraints);
+
+    Ok(HttpResponse::Ok().json(solver.suggestion()))
 }
 
-#[derive(Clone, Debug)]
-struct Clue {
-    c: char,
-    occur: u32,
-    hints: Vec<Hint>
+async fn constraints(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    state.constraints.to_compact_string()
 }
 
-impl Clue {
-    fn from_input(guess: &str, inp: &str) -> Vec<Clue> {
-        let mut chars: Vec<char> = guess.chars().collect();
-        chars.sort();
-        chars.dedup();
+async fn occurrences(body: web::Json<Vec<OccurrenceRequest>>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
 
-        let input_chars: Vec<char> = inp.chars().collect();
+    validate_occurrences(&body, &state.all_words)?;
+    for occurrence in body.iter() {
+        state.constrain(occurrence.letter, occurrence.min.unwrap_or(0), occurrence.max);
+    }
 
-        let mut clues = Vec::new();
-        
-        for c in chars {
-            let matches = guess.match_indices(c);
-            let mut hints = vec![Hint::Unset; WORD_LEN];
+    Ok(HttpResponse::Ok().json(state.suggestion()))
+}
 
-            let mut correct = 0;
-            let mut wrong_place = 0;
-            let mut wrong = 0;
+// The opening suggestion for each reset, leaving the server's game alone.
+// No sessions are kept: one throwaway solver is reset for each in turn.
+async fn reset_batch(body: web::Json<ResetBatchRequest>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    body.validate()?;
+    let mut solver = state.lock().expect("Error locking mutex").fresh();
 
-            for (idx, _) in matches {
-                if input_chars[idx] == 'c' {
-                    correct += 1;
-                    hints[idx] = Hint::Yes;
-                }
-                else if input_chars[idx] == 'w' {
-                    wrong_place += 1;
-                    hints[idx] = Hint::No;
-                }
-                else {
-                    wrong += 1;
-                    hints[idx] = Hint::No;
-                }
+    let suggestions: Vec<ResetSuggestion> = body.resets.iter()
+        .map(|reset| {
+            let mode = reset.mode.unwrap_or(FirstLetter::Green);
+            solver.reset_with(reset.letter, mode);
+            ResetSuggestion {
+                letter: reset.letter,
+                mode: mode.name(),
+                suggestion: solver.suggestion(),
             }
+        })
+        .collect();
 
-            let mut replace = Hint::Maybe;
-            if wrong > 0 {
-                replace = Hint::No;
-            }
+    Ok(HttpResponse::Ok().json(suggestions))
+}
 
-            for hint in &mut hints {
-                if *hint == Hint::Unset {
-                    *hint = replace.clone();
-                }
-            }
+async fn suggest_batch(body: web::Json<BatchRequest>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let state = state.lock().expect("Error locking mutex");
 
-            let mut occur = 0;
-            occur += correct;
-            if wrong_place > 0 {
-                occur += 1;
-            }
+    body.validate(&state.all_words)?;
 
-            clues.push(Clue {
-                c,
-                occur,
-                hints,
-            });
-        }
+    let suggestions: Vec<BatchSuggestion> = body.feedbacks.iter()
+        .map(|feedback| BatchSuggestion {
+            feedback: feedback.clone(),
+            suggestion: state.preview(&body.guess, feedback),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(suggestions))
+}
 
-        clues
+async fn strategy(path: web::Path<(String,)>, state: web::Data<AppState>) -> impl Responder {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    match Strategy::from_name(&path.0) {
+        Some(strategy) => {
+            state.strategy = strategy;
+            format!("Strategy set to {}", path.0)
+        },
+        None => format!("Unknown strategy: {}", path.0),
     }
 }
 
-#[derive(Clone)]
-struct State {
-    all_words: Dictionary,
-    valid_words: Dictionary,
-    valid_guesses: Dictionary,
+async fn set_dictionary(path: web::Path<(String,)>, state: web::Data<AppState>, dictionaries: web::Data<Dictionaries>) -> impl Responder {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    match dictionaries.get(&path.0) {
+        Some(all_words) => {
+            let all_answers = match state.config.answer_limit {
+                Some(n) => all_words.top(n),
+                None => all_words.clone(),
+            };
+            state.set_dictionary(&path.0, all_words.clone(), all_answers);
+            format!("Dictionary set to {}", path.0)
+        },
+        None => format!("Unknown dictionary: {}", path.0),
+    }
+}
+
+// Reads the weights file again, keeping the old weights if it's invalid.
+async fn reload_weights(state: web::Data<AppState>) -> Result<String, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    let path = state.config.weights.clone().ok_or_else(|| {
+        LingoError::Validation(vec![FieldError::new("weights", "no weights file is configured")])
+    })?;
+    let weights = Weights::load(&path).map_err(|err| {
+        LingoError::Validation(vec![FieldError::new("weights", format!("could not read {}: {}", path, err))])
+    })?;
+    let count = weights.len();
+    state.set_weights(weights);
+
+    Ok(format!("Loaded {} weights from {}", count, path))
+}
+
+async fn diag_report(state: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(state.diag().report())
+}
+
+// Requests are timed by their first path segment, so each route with path
+// parameters is counted once rather than per word.
+fn endpoint_name(path: &str) -> String {
+    let segment = path.trim_start_matches('/').split('/').next().unwrap_or("");
+    format!("/{}", segment)
+}
+
+// Upgrades to a websocket carrying one game, see `lingo::ws::Session`.
+async fn websocket(req: HttpRequest, payload: web::Payload, state: web::Data<AppState>) -> Result<HttpResponse, actix_web::Error> {
+    let mut response = actix_http::ws::handshake(req.head())?;
+    let solver = state.lock().expect("Error locking mutex").fresh();
+
+    Ok(response.streaming(Session::new(solver).run(payload)))
+}
+
+async fn ranked(query: web::Query<RankedQuery>, state: web::Data<AppState>) -> impl Responder {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.ranking(query.limit.unwrap_or(DEFAULT_RANKED_LIMIT)))
+}
+
+async fn progress(state: web::Data<AppState>) -> impl Responder {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.progress())
+}
+
+async fn metrics(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(state.metrics.render())
+}
+
+async fn ties(state: web::Data<AppState>) -> impl Responder {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.ties())
+}
+
+async fn guaranteed_finish(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.guaranteed_finish())
+}
+
+async fn distinguishing_set(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.distinguishing_set())
+}
+
+async fn complete(path: web::Path<(String,)>, state: web::Data<AppState>) -> Result<String, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    state.complete(&path.0)
+}
+
+async fn coach(path: web::Path<(String,)>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let mut state = state.lock().expect("Error locking mutex");
+
+    Ok(HttpResponse::Ok().json(state.regret(&path.0)?))
 }
 
-fn get_guess(state: &mut State) -> String {
-    let len = state.valid_words.words.len();
-    if len == 0 {
-        return String::from("No possible words!");
+async fn best_candidate(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.best_candidate_guess())
+}
+
+async fn closest_pair(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.closest_pair())
+}
+
+async fn remaining(query: web::Query<PageQuery>, state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    let cap = state.config.remaining_limit;
+    let limit = query.limit.unwrap_or(cap).min(cap);
+    HttpResponse::Ok().json(state.remaining_page(query.offset.unwrap_or(0), limit))
+}
+
+// `position` picks the letter to group by, the first by default.
+#[derive(Deserialize)]
+struct GroupQuery {
+    position: Option<usize>,
+}
+
+// Every candidate, grouped by its letter at `position`.
+async fn remaining_grouped(query: web::Query<GroupQuery>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let state = state.lock().expect("Error locking mutex");
+
+    let position = query.position.unwrap_or(0);
+    if position >= WORD_LEN {
+        return Err(LingoError::Validation(vec![
+            FieldError::new("position", format!("must be less than {}", WORD_LEN)),
+        ]));
     }
-    else if len == 1{
-        return state.valid_words.words[0].word.clone();
+    Ok(HttpResponse::Ok().json(state.valid_words.grouped_by(position)))
+}
+
+async fn play_new(state: web::Data<AppState>, play: web::Data<PlayState>) -> Result<HttpResponse, LingoError> {
+    let state = state.lock().expect("Error locking mutex");
+    let mut play = play.lock().expect("Error locking mutex");
+
+    match play.new_game(&state.all_answers, state.config.play_attempts) {
+        Some(start) => Ok(HttpResponse::Ok().json(start)),
+        None => Err(LingoError::Validation(vec![FieldError::new("answers", "there are no answers to play")])),
     }
+}
+
+async fn play_guess(path: web::Path<(String,)>, state: web::Data<AppState>, play: web::Data<PlayState>) -> Result<HttpResponse, LingoError> {
+    let state = state.lock().expect("Error locking mutex");
+    let mut play = play.lock().expect("Error locking mutex");
 
-    let freq = state.valid_words.char_frequency();
-    state.valid_guesses.sort(&freq).word
+    let mut errors = Vec::new();
+    validate_guess("guess", &path.0, &state.all_words, &mut errors);
+    check(errors)?;
+
+    match play.guess(&path.0) {
+        Some(result) => Ok(HttpResponse::Ok().json(result)),
+        None => Err(LingoError::Validation(vec![FieldError::new("game", "no game in progress, start one with /play/new")])),
+    }
+}
+
+async fn stats(play: web::Data<PlayState>) -> impl Responder {
+    let play = play.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(play.stats.report())
 }
 
-async fn reset(path: web::Path<(char,)>, state: web::Data<AppState>) -> impl Responder {
+async fn rewind(path: web::Path<(usize,)>, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
     let mut state = state.lock().expect("Error locking mutex");
 
-    let mut words = state.all_words.clone();
-    words.filter(&Clue {
-        c: path.0,
-        occur: 1,
-        hints: vec![Hint::Yes, Hint::Maybe, Hint::Maybe, Hint::Maybe, Hint::Maybe]
-    });
+    Ok(HttpResponse::Ok().json(state.rewind(path.0)?))
+}
 
-    state.valid_words = words.clone();
-    state.valid_guesses = words;
+// The game as a blob for `POST /snapshot` on another server, see
+// `Snapshot`.
+async fn export_snapshot(state: web::Data<AppState>) -> Result<String, LingoError> {
+    let state = state.lock().expect("Error locking mutex");
 
-    get_guess(&mut state)
+    match Snapshot::of(&state) {
+        Some(snapshot) => Ok(snapshot.encode()),
+        None => Err(LingoError::Validation(vec![
+            FieldError::new("snapshot", "only games started with a reset can be exported"),
+        ])),
+    }
 }
 
-async fn hint(path: web::Path<(String, String)>, state: web::Data<AppState>) -> impl Responder {
+async fn import_snapshot(body: String, state: web::Data<AppState>) -> Result<HttpResponse, LingoError> {
+    let snapshot = Snapshot::decode(&body)?;
     let mut state = state.lock().expect("Error locking mutex");
 
-    let clues = Clue::from_input(&path.0, &path.1);
-    for clue in clues {
-        state.valid_words.filter(&clue);
+    Ok(HttpResponse::Ok().json(snapshot.restore(&mut state)?))
+}
+
+#[derive(Serialize)]
+struct LengthCheck {
+    word: String,
+    valid: bool,
+}
+
+// Past benchmark runs from `config.benchmark_history`, oldest first.
+async fn benchmark_history(query: web::Query<HistoryQuery>, state: web::Data<AppState>) -> HttpResponse {
+    let state = state.lock().expect("Error locking mutex");
+
+    match past_runs(&state, query.all.unwrap_or(false)) {
+        Ok(runs) => HttpResponse::Ok().json(runs),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+// How many words each of `config.length_dictionaries` holds.
+async fn length_counts(lengths: web::Data<LengthDictionaries>) -> impl Responder {
+    let counts: BTreeMap<usize, usize> = lengths.iter()
+        .map(|(&len, dictionary)| (len, dictionary.words.len()))
+        .collect();
+
+    HttpResponse::Ok().json(counts)
+}
+
+// Whether `word` is in the list for words of `length` letters.
+async fn length_check(path: web::Path<(usize, String)>, lengths: web::Data<LengthDictionaries>) -> Result<HttpResponse, LingoError> {
+    let (len, word) = path.into_inner();
+    let dictionary = lengths.get(&len).ok_or_else(|| LingoError::Validation(vec![
+        FieldError::new("length", format!("has no dictionary file for {} letter words", len)),
+    ]))?;
+
+    if word.chars().count() != len {
+        return Err(LingoError::Validation(vec![
+            FieldError::new("word", format!("must be {} letters long", len)),
+        ]));
+    }
+
+    Ok(HttpResponse::Ok().json(LengthCheck {
+        valid: dictionary.contains(&word),
+        word,
+    }))
+}
+
+async fn history(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(&state.history)
+}
+
+async fn trace(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(&state.trace)
+}
+
+// Rows are the letters 'a' to 'z', columns are positions in the word.
+async fn matrix(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.valid_words.letter_matrix())
+}
+
+// The letter counts per position that frequency scores come from, for
+// `dictionary::load_profile`.
+async fn profile(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    let profile: BTreeMap<char, Vec<u32>> = state.valid_words.char_frequency().into_iter().collect();
+    HttpResponse::Ok().json(profile)
+}
+
+// The letter every candidate has at each position, or null where they
+// differ.
+async fn determined(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.valid_words.determined_positions())
+}
+
+// The chance each letter is the answer's letter at each position, one map
+// per position.
+async fn probabilities(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.valid_words.position_probabilities())
+}
+
+async fn entropy(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    HttpResponse::Ok().json(state.valid_words.position_entropy())
+}
+
+// A `lingo pipe` script for the current game. It has the reset and the
+// hints but not `/occurrences` facts, which aren't kept as turns.
+async fn replay_script(state: web::Data<AppState>) -> impl Responder {
+    let state = state.lock().expect("Error locking mutex");
+
+    state.export_replay()
+}
+
+fn pipe(mut solver: Solver) -> std::io::Result<()> {
+    let mut script = String::new();
+    std::io::stdin().read_to_string(&mut script)?;
+
+    for line in solver.replay(&script) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+// Plays every answer and writes the ones that took the most guesses to
+// `path`, for use as the `answers` config option.
+fn hard_words(mut solver: Solver, count: usize, path: &str) -> std::io::Result<()> {
+    let outcomes = simulate::benchmark(&mut solver);
+    let unsolved = outcomes.iter().filter(|o| o.guesses.is_none()).count();
+    if let Some(average) = simulate::average_guesses(&outcomes) {
+        println!("Played {} answers: {:.3} guesses on average, {} unsolved", outcomes.len(), average, unsolved);
+    }
+
+    record_run(&solver, &outcomes);
+
+    let hardest = simulate::hardest(outcomes, count);
+    let mut file = File::create(path)?;
+    for outcome in &hardest {
+        writeln!(file, "{}", outcome.answer)?;
+    }
+
+    println!("Wrote the {} hardest words to {}", hardest.len(), path);
+    Ok(())
+}
+
+// Plays every answer, writing an `answer,guesses,solved` row to `path` as
+// each game ends. Unsolved games have an empty guess count.
+fn benchmark_csv(mut solver: Solver, path: &str) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let dictionary = solver.config.combined.clone().unwrap_or_else(|| solver.config.dictionary.clone());
+    writeln!(file, "# strategy={},dictionary={}", solver.strategy.name(), dictionary)?;
+    writeln!(file, "answer,guesses,solved")?;
+
+    let mut outcomes = Vec::new();
+    simulate::benchmark_each(&mut solver, |outcome| {
+        let guesses = outcome.guesses.map(|n| n.to_string()).unwrap_or_default();
+        writeln!(file, "{},{},{}", outcome.answer, guesses, outcome.guesses.is_some())?;
+        outcomes.push(outcome);
+        file.flush()
+    })?;
+
+    println!("Wrote {} benchmark results to {}", outcomes.len(), path);
+    record_run(&solver, &outcomes);
+    Ok(())
+}
+
+// Appends a benchmark's summary to `config.benchmark_history`. A failure
+// is reported but doesn't fail the benchmark.
+fn record_run(solver: &Solver, outcomes: &[simulate::Outcome]) {
+    let path = &solver.config.benchmark_history;
+    if let Err(err) = BenchmarkRun::new(solver, outcomes).append(path) {
+        eprintln!("Could not write benchmark history {}: {}", path, err);
     }
+}
 
-    get_guess(&mut state)
+// The runs in `config.benchmark_history`, only those over the solver's
+// word lists unless `all` is set.
+fn past_runs(solver: &Solver, all: bool) -> std::io::Result<Vec<BenchmarkRun>> {
+    let dictionary = opener::fingerprint(&solver.all_words, &solver.all_answers);
+    let mut runs = history::load(&solver.config.benchmark_history)?;
+    if !all {
+        runs.retain(|run| run.dictionary == dictionary);
+    }
+    Ok(runs)
+}
+
+// Prints past benchmark runs, one per line.
+fn bench_history(solver: Solver, all: bool) -> std::io::Result<()> {
+    let runs = past_runs(&solver, all)?;
+    if runs.is_empty() {
+        println!("No benchmark runs in {}", solver.config.benchmark_history);
+    }
+
+    for run in &runs {
+        let average = run.average.map(|a| format!("{:.3}", a)).unwrap_or_else(|| String::from("-"));
+        let worst = run.worst.map(|w| w.to_string()).unwrap_or_else(|| String::from("-"));
+        println!("{} {} dictionary={} answers={} average={} worst={} unsolved={}",
+            run.time, run.strategy, run.dictionary, run.answers, average, worst, run.unsolved);
+    }
+    Ok(())
+}
+
+// Checks that the feedback the grader gives never filters out the answer,
+// over random games with the dictionary's words. Exits with an error and
+// the game that failed otherwise.
+fn fuzz(mut solver: Solver, rounds: usize, seed: u64) -> std::io::Result<()> {
+    match simulate::fuzz_clues(&mut solver, rounds, seed) {
+        Ok(()) => {
+            println!("Played {} games with seed {}: the answer always survived", rounds, seed);
+            Ok(())
+        },
+        Err(failure) => {
+            eprintln!("With seed {}, the answer {} was filtered out after:", seed, failure.answer);
+            for (guess, feedback) in &failure.hints {
+                eprintln!("{} {}", guess, feedback);
+            }
+            std::process::exit(1);
+        },
+    }
+}
+
+// Computes the first guesses for `strategy` and writes them to the opener
+// cache the server reads at startup.
+fn precompute_opener(mut solver: Solver, strategy: Strategy) -> std::io::Result<()> {
+    solver.strategy = strategy;
+    solver.opener_cache = None;
+    // This runs offline, so the strategy gets all the time it needs.
+    solver.config.strategy_timeout_ms = u64::MAX;
+
+    let cache = OpenerCache::compute(&mut solver);
+    cache.save(&solver.config.opener_cache)?;
+
+    println!("Wrote {} {} openers to {}", cache.openers.len(), cache.strategy, solver.config.opener_cache);
+    Ok(())
+}
+
+fn load_opener_cache(solver: &mut Solver) {
+    let cache = match OpenerCache::load(&solver.config.opener_cache) {
+        Ok(cache) => cache,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => {
+            eprintln!("Could not read opener cache {}: {}", solver.config.opener_cache, err);
+            return;
+        },
+    };
+
+    if cache.matches(&solver.all_words, &solver.all_answers) {
+        solver.opener_cache = Some(cache);
+    }
+    else {
+        eprintln!("Ignoring opener cache {}: it was made for a different dictionary", solver.config.opener_cache);
+    }
+}
+
+fn load_weights(solver: &mut Solver) {
+    if let Some(path) = &solver.config.weights {
+        match Weights::load(path) {
+            Ok(weights) => solver.set_weights(weights),
+            Err(err) => eprintln!("Could not read weights file {}: {}", path, err),
+        }
+    }
+}
+
+fn load_answers(path: &str, all_words: &Dictionary) -> Dictionary {
+    let mut answers = match File::open(path) {
+        Ok(file) => Dictionary::from_file(file, &all_words.charset),
+        Err(err) => {
+            eprintln!("Could not open answers file {}: {}", path, err);
+            std::process::exit(1);
+        },
+    };
+
+    let known: HashSet<&str> = all_words.words.iter().map(|w| w.word.as_str()).collect();
+    let before = answers.words.len();
+    answers.retain_matching(|w| known.contains(w.word.as_str()));
+    if answers.words.len() < before {
+        eprintln!("Skipped {} answers that aren't in the dictionary", before - answers.words.len());
+    }
+
+    answers
+}
+
+fn load_combined(path: &str, config: &Config) -> (Dictionary, Dictionary) {
+    Dictionary::load_combined(path, &config.charset()).unwrap_or_else(|err| {
+        eprintln!("Could not load combined word list {}: {}", path, err);
+        std::process::exit(1);
+    })
+}
+
+fn load_dictionary(config: &Config) -> Dictionary {
+    if let Some(symbols) = &config.codeword_symbols {
+        return Dictionary::codewords(symbols, &config.charset()).unwrap_or_else(|err| {
+            eprintln!("Invalid codeword_symbols: {}", err);
+            std::process::exit(1);
+        });
+    }
+
+    match File::open(&config.dictionary) {
+        Ok(file) => Dictionary::from_file(file, &config.charset()),
+        Err(err) => {
+            eprintln!("Could not open dictionary file {}: {}", config.dictionary, err);
+
+            embedded_dictionary(config).unwrap_or_else(|| {
+                eprintln!("Set \"dictionary\" in lingo.json (or the file named by LINGO_CONFIG) to a word list with one word per line.");
+                std::process::exit(1);
+            })
+        },
+    }
+}
+
+// Every list in `config.length_dictionaries`, exiting if one can't be read
+// or the game's length is missing.
+fn load_length_dictionaries(config: &Config) -> LengthDictionaries {
+    if config.length_dictionaries.is_empty() {
+        return BTreeMap::new();
+    }
+    if !config.length_dictionaries.contains_key(&WORD_LEN) {
+        eprintln!("length_dictionaries has no file for {} letter words, which the game uses", WORD_LEN);
+        std::process::exit(1);
+    }
+
+    let mut dictionaries = BTreeMap::new();
+    for (len, result) in dictionary::load_lengths(&config.length_dictionaries, &config.charset()) {
+        let path = &config.length_dictionaries[&len];
+        match result {
+            Ok(dictionary) => {
+                println!("Loaded {} {} letter words from {}", dictionary.words.len(), len, path);
+                dictionaries.insert(len, dictionary);
+            },
+            Err(err) => {
+                eprintln!("Could not load {} letter dictionary {}: {}", len, path, err);
+                std::process::exit(1);
+            },
+        }
+    }
+
+    dictionaries
+}
+
+fn load_dictionaries(config: &Config) -> Dictionaries {
+    let mut dictionaries = BTreeMap::new();
+
+    for (name, result) in dictionary::load_named(&config.dictionaries, &config.charset()) {
+        match result {
+            Ok(dictionary) => {
+                dictionaries.insert(name, dictionary);
+            },
+            Err(err) => eprintln!("Could not load dictionary {} from {}: {}", name, config.dictionaries[&name], err),
+        }
+    }
+
+    dictionaries
+}
+
+#[cfg(feature = "embedded-dictionary")]
+fn embedded_dictionary(config: &Config) -> Option<Dictionary> {
+    eprintln!("Using the embedded dictionary instead");
+    Some(Dictionary::from_text(lingo::dictionary::EMBEDDED_DICTIONARY, &config.charset()))
+}
+
+#[cfg(not(feature = "embedded-dictionary"))]
+fn embedded_dictionary(_config: &Config) -> Option<Dictionary> {
+    None
+}
+
+// Removes a socket left behind by a server that didn't shut down cleanly,
+// refusing to touch anything that isn't a socket or is still in use.
+fn remove_stale_socket(path: &str) -> std::io::Result<()> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} exists and is not a socket", path)));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AddrInUse, format!("another server is listening on {}", path)));
+    }
+
+    eprintln!("Removing stale socket {}", path);
+    std::fs::remove_file(path)
 }
 
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
-    let state = web::Data::new(Mutex::new(State {
-        all_words: Dictionary::from_file(File::open("words_alpha.txt").expect("Error opening dict file")),
-        valid_words: Dictionary::empty(),
-        valid_guesses: Dictionary::empty(),
-    }));
-    HttpServer::new(move || {
+    let config = Config::load();
+    if let Err(err) = config.validate() {
+        eprintln!("Invalid config: {}", err);
+        std::process::exit(1);
+    }
+
+    let lengths = load_length_dictionaries(&config);
+    let (all_words, all_answers) = match &config.combined {
+        Some(path) => load_combined(path, &config),
+        None => {
+            let all_words = match lengths.get(&WORD_LEN) {
+                Some(dictionary) => dictionary.clone(),
+                None => load_dictionary(&config),
+            };
+            if all_words.rejected > 0 {
+                eprintln!("Skipped {} dictionary words with invalid characters", all_words.rejected);
+            }
+            let all_answers = match &config.answers {
+                Some(path) => load_answers(path, &all_words),
+                None => all_words.clone(),
+            };
+            (all_words, all_answers)
+        },
+    };
+    let all_answers = match config.answer_limit {
+        Some(n) => all_answers.top(n),
+        None => all_answers,
+    };
+
+    let mut solver = Solver::new(&config, all_words, all_answers);
+    if let Err(err) = solver.check_openers() {
+        eprintln!("Invalid config: {}", err);
+        std::process::exit(1);
+    }
+    load_opener_cache(&mut solver);
+    load_weights(&mut solver);
+
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("pipe") => return pipe(solver),
+        Some("hard-words") => {
+            let count = args.get(2)
+                .map(|n| n.parse().expect("Word count must be a number"))
+                .unwrap_or(DEFAULT_HARD_WORDS);
+            let path = args.get(3).map(String::as_str).unwrap_or(DEFAULT_HARD_WORDS_PATH);
+            return hard_words(solver, count, path);
+        },
+        Some("benchmark-csv") => {
+            let path = args.get(2).map(String::as_str).unwrap_or(DEFAULT_BENCHMARK_CSV_PATH);
+            return benchmark_csv(solver, path);
+        },
+        Some("bench-history") => {
+            let all = args.get(2).map(String::as_str) == Some("all");
+            return bench_history(solver, all);
+        },
+        Some("fuzz") => {
+            let rounds = args.get(2)
+                .map(|n| n.parse().expect("Round count must be a number"))
+                .unwrap_or(DEFAULT_FUZZ_ROUNDS);
+            let seed = args.get(3)
+                .map(|n| n.parse().expect("Seed must be a number"))
+                .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(1));
+            return fuzz(solver, rounds, seed);
+        },
+        Some("precompute-opener") => {
+            let name = args.get(2).map(String::as_str).unwrap_or("frequency");
+            let strategy = Strategy::from_name(name).unwrap_or_else(|| {
+                eprintln!("Unknown strategy: {}", name);
+                std::process::exit(1);
+            });
+            return precompute_opener(solver, strategy);
+        },
+        _ => {},
+    }
+
+    let dictionaries = web::Data::new(load_dictionaries(&config));
+    let lengths = web::Data::new(lengths);
+    let diag = Arc::new(Diag::new(config.diag));
+    let state = web::Data::new(TimedMutex::new(solver, diag.clone()));
+    let play = web::Data::new(Mutex::new(Play::default()));
+    let server = HttpServer::new(move || {
+        let diag = diag.clone();
+        let diag_enabled = diag.enabled();
         App::new()
+            .wrap_fn(move |req, srv| {
+                let start = Instant::now();
+                let endpoint = endpoint_name(req.path());
+                let diag = diag.clone();
+                let response = srv.call(req);
+                async move {
+                    let response = response.await?;
+                    if response.status() != StatusCode::NOT_FOUND {
+                        diag.record_request(&endpoint, start.elapsed());
+                    }
+                    Ok(response)
+                }
+            })
+            .configure(|cfg| if diag_enabled {
+                cfg.route("/diag", web::get().to(diag_report));
+            })
             .app_data(state.clone())
+            .app_data(play.clone())
+            .app_data(dictionaries.clone())
+            .app_data(lengths.clone())
+            .app_data(web::JsonConfig::default().error_handler(|err, _| {
+                LingoError::Validation(vec![FieldError::new("body", err.to_string())]).into()
+            }))
+            .app_data(web::QueryConfig::default().error_handler(|err, _| {
+                LingoError::Validation(vec![FieldError::new("query", err.to_string())]).into()
+            }))
             .route("/reset/{letter}", web::get().to(reset))
+            .route("/reset/{letter}", web::post().to(reset_with_settings))
+            .route("/openers/{letter}", web::get().to(openers))
+            .route("/next.txt", web::get().to(next_text))
             .route("/hint/{word}/{hint}", web::get().to(hint))
-    })
-    .bind("0.0.0.0:8088")?
-    .run()
-    .await
+            .route("/hint/{cased}", web::get().to(hint_cased))
+            .route("/hint", web::post().to(post_hint))
+            .route("/ws", web::get().to(websocket))
+            .route("/suggest", web::post().to(suggest))
+            .route("/suggest/{constraints}", web::get().to(suggest_compact))
+            .route("/constraints", web::get().to(constraints))
+            .route("/occurrences", web::post().to(occurrences))
+            .route("/suggest-batch", web::post().to(suggest_batch))
+            .route("/reset-batch", web::post().to(reset_batch))
+            .route("/strategy/{name}", web::get().to(strategy))
+            .route("/dictionary/{name}", web::get().to(set_dictionary))
+            .route("/ranked", web::get().to(ranked))
+            .route("/progress", web::get().to(progress))
+            .route("/metrics", web::get().to(metrics))
+            .route("/ties", web::get().to(ties))
+            .route("/weights/reload", web::post().to(reload_weights))
+            .route("/complete/{template}", web::get().to(complete))
+            .route("/coach/{guess}", web::get().to(coach))
+            .route("/guaranteed-finish", web::get().to(guaranteed_finish))
+            .route("/distinguishing-set", web::get().to(distinguishing_set))
+            .route("/closest-pair", web::get().to(closest_pair))
+            .route("/best-candidate", web::get().to(best_candidate))
+            .route("/remaining", web::get().to(remaining))
+            .route("/remaining-grouped", web::get().to(remaining_grouped))
+            .route("/play/new", web::get().to(play_new))
+            .route("/play/guess/{word}", web::get().to(play_guess))
+            .route("/stats", web::get().to(stats))
+            .route("/history", web::get().to(history))
+            .route("/rewind/{turn}", web::post().to(rewind))
+            .route("/snapshot", web::get().to(export_snapshot))
+            .route("/snapshot", web::post().to(import_snapshot))
+            .route("/grade/{guess}/{answer}", web::get().to(grade_words))
+            .route("/benchmark-history", web::get().to(benchmark_history))
+            .route("/lengths", web::get().to(length_counts))
+            .route("/lengths/{length}/{word}", web::get().to(length_check))
+            .route("/trace", web::get().to(trace))
+            .route("/matrix", web::get().to(matrix))
+            .route("/profile", web::get().to(profile))
+            .route("/entropy", web::get().to(entropy))
+            .route("/probabilities", web::get().to(probabilities))
+            .route("/determined", web::get().to(determined))
+            .route("/replay-script", web::get().to(replay_script))
+    });
+
+    let server = match config.workers {
+        Some(0) => {
+            eprintln!("Invalid config: workers must be at least 1");
+            std::process::exit(1);
+        },
+        Some(workers) => server.workers(workers),
+        None => server,
+    };
+    let server = match &config.socket {
+        Some(path) => {
+            remove_stale_socket(path)?;
+            server.bind_uds(path)?
+        },
+        None => server.bind("0.0.0.0:8088")?,
+    };
+
+    server.run().await
 }
 
-/*
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut valid_words = Dictionary::from_file(File::open("words_alpha.txt")?);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test;
+    use lingo::dictionary::Charset;
 
-    let c = input::<char>().msg("What is the first character? ").get()
-        .to_lowercase()
-        .next()
-        .expect("Error converting to lowercase");
+    fn state() -> web::Data<AppState> {
+        let words = Dictionary::from_text("quart\nquate\nquake\nquail\nquiet\nqueen\n", &Charset::default());
+        let solver = Solver::new(&Config::default(), words.clone(), words);
+        web::Data::new(TimedMutex::new(solver, Arc::new(Diag::new(false))))
+    }
 
-    valid_words.filter(&Clue {
-        c,
-        occur: 1,
-        hints: vec![Hint::Yes, Hint::Maybe, Hint::Maybe, Hint::Maybe, Hint::Maybe]
-    });
+    async fn call(state: &web::Data<AppState>, request: test::TestRequest) -> (StatusCode, String) {
+        let mut app = test::init_service(App::new()
+            .app_data(state.clone())
+            .route("/reset/{letter}", web::get().to(reset))
+            .route("/hint/{word}/{hint}", web::get().to(hint))
+            .route("/occurrences", web::post().to(occurrences))).await;
+        let response = test::call_service(&mut app, request.to_request()).await;
+        let status = response.status();
+        let body = test::read_body(response).await;
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
 
-    let mut possible_guesses = valid_words.clone();
+    async fn get(state: &web::Data<AppState>, uri: &str) -> (StatusCode, String) {
+        call(state, test::TestRequest::get().uri(uri)).await
+    }
 
-    loop {
-        let len = valid_words.words.len();
-        if len == 0 {
-            println!("No more possible words! Did you make a mistake?");
-            break;
-        }
-        else if len == 1{
-            println!("I got it! Your word is: {}", valid_words.words[0].word);
-            break;
-        }
+    #[actix_rt::test]
+    async fn reset_with_a_first_turn_matches_reset_then_hint() {
+        let combined = state();
+        let (status, guess) = get(&combined, "/reset/q?guess=quiet&feedback=ccnnc").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let separate = state();
+        get(&separate, "/reset/q").await;
+        let (_, expected) = get(&separate, "/hint/quiet/ccnnc").await;
 
-        let freq = valid_words.char_frequency();
-        let guess = possible_guesses.sort(&freq);
-        println!("I guess {}", guess.word);
+        assert_eq!(guess, expected);
+        let (combined, separate) = (combined.lock().expect("Error locking mutex"), separate.lock().expect("Error locking mutex"));
+        assert_eq!(combined.history.len(), 1);
+        let words = |solver: &Solver| solver.valid_words.words.iter().map(|w| w.word.clone()).collect::<Vec<_>>();
+        assert_eq!(words(&combined), words(&separate));
+    }
 
-        let inp = input::<String>().msg("What is your hint? ").get();
+    #[actix_rt::test]
+    async fn reset_rejects_a_guess_without_feedback() {
+        let (status, _) = get(&state(), "/reset/q?guess=quiet").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
 
-        let clues = Clue::from_input(&guess.word, &inp);
-        for clue in clues {
-            valid_words.filter(&clue);
-        }
+    #[actix_rt::test]
+    async fn occurrences_reject_a_minimum_above_the_maximum() {
+        let state = state();
+        get(&state, "/reset/q").await;
+
+        let body = serde_json::json!([{ "letter": "e", "min": 2, "max": 1 }]);
+        let (status, response) = call(&state, test::TestRequest::post().uri("/occurrences").set_json(&body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.contains("[0].max"), "responded {}", response);
+        assert_eq!(state.lock().expect("Error locking mutex").valid_words.words.len(), 6);
     }
 
-    Ok(())
-}*/
+    #[actix_rt::test]
+    async fn hint_rejects_a_guess_of_the_wrong_length() {
+        let state = state();
+        get(&state, "/reset/q").await;
+
+        let (status, response) = get(&state, "/hint/quarts/cccccn").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.contains("guess"), "responded {}", response);
+
+        let (status, response) = get(&state, "/hint/quart/cccc").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.contains("feedback"), "responded {}", response);
+
+        assert!(state.lock().expect("Error locking mutex").history.is_empty());
+    }
+}
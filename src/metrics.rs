@@ -0,0 +1,58 @@
+use std::fmt::Write;
+
+// Upper bounds of the eliminated-candidates histogram buckets.
+const BUCKETS: [u64; 10] = [0, 1, 2, 5, 10, 20, 50, 100, 200, 500];
+
+// Counters for the Prometheus exporter, kept across games.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    pub hints: u64,
+    pub eliminated: u64,
+    // Hints per bucket of `BUCKETS`, with a last one for larger counts.
+    bucket_counts: [u64; BUCKETS.len() + 1],
+}
+
+impl Metrics {
+    pub fn record_hint(&mut self, eliminated: usize) {
+        let eliminated = eliminated as u64;
+        let bucket = BUCKETS.iter().position(|&b| eliminated <= b).unwrap_or(BUCKETS.len());
+
+        self.hints += 1;
+        self.eliminated += eliminated;
+        self.bucket_counts[bucket] += 1;
+    }
+
+    pub fn average_eliminated(&self) -> f64 {
+        if self.hints == 0 {
+            return 0.0;
+        }
+
+        self.eliminated as f64 / self.hints as f64
+    }
+
+    // The Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out += "# HELP lingo_hints_total Hints processed.\n";
+        out += "# TYPE lingo_hints_total counter\n";
+        let _ = writeln!(out, "lingo_hints_total {}", self.hints);
+
+        out += "# HELP lingo_hint_eliminated Candidates eliminated by each hint.\n";
+        out += "# TYPE lingo_hint_eliminated histogram\n";
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(&self.bucket_counts) {
+            cumulative += count;
+            let _ = writeln!(out, "lingo_hint_eliminated_bucket{{le=\"{}\"}} {}", bound, cumulative);
+        }
+        let _ = writeln!(out, "lingo_hint_eliminated_bucket{{le=\"+Inf\"}} {}", self.hints);
+        let _ = writeln!(out, "lingo_hint_eliminated_sum {}", self.eliminated);
+        let _ = writeln!(out, "lingo_hint_eliminated_count {}", self.hints);
+
+        out += "# HELP lingo_hint_eliminated_average Mean candidates eliminated per hint.\n";
+        out += "# TYPE lingo_hint_eliminated_average gauge\n";
+        let _ = writeln!(out, "lingo_hint_eliminated_average {}", self.average_eliminated());
+
+        out
+    }
+}
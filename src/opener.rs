@@ -0,0 +1,76 @@
+use crate::dictionary::Dictionary;
+use crate::solver::Solver;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+
+// The letters a game can be reset with.
+const LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+// The first guess after a green reset with each letter, as computed for one
+// dictionary and strategy.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenerCache {
+    // `fingerprint` of the guesses and answers, in hex.
+    pub dictionary: String,
+    pub strategy: String,
+    pub openers: BTreeMap<char, String>,
+}
+
+impl OpenerCache {
+    // Plays the first turn after every reset letter. This changes the
+    // solver's game.
+    pub fn compute(solver: &mut Solver) -> OpenerCache {
+        let mut openers = BTreeMap::new();
+
+        for c in LETTERS.chars() {
+            solver.reset(c);
+            if let Some(guess) = solver.next_guess() {
+                openers.insert(c, guess);
+            }
+        }
+
+        OpenerCache {
+            dictionary: fingerprint(&solver.all_words, &solver.all_answers),
+            strategy: solver.strategy.name().to_string(),
+            openers,
+        }
+    }
+
+    pub fn load(path: &str) -> io::Result<OpenerCache> {
+        let file = File::open(path)?;
+        serde_json::from_reader(file).map_err(io::Error::from)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self).map_err(io::Error::from)
+    }
+
+    // Whether the cache was computed for these word lists.
+    pub fn matches(&self, all_words: &Dictionary, all_answers: &Dictionary) -> bool {
+        self.dictionary == fingerprint(all_words, all_answers)
+    }
+}
+
+// An FNV-1a hash of the guesses and answers in order, which changes whenever
+// either list does.
+pub fn fingerprint(all_words: &Dictionary, all_answers: &Dictionary) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for dictionary in &[all_words, all_answers] {
+        for word in &dictionary.words {
+            for byte in word.word.bytes().chain(std::iter::once(b'\n')) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash ^= 0xff;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    format!("{:016x}", hash)
+}
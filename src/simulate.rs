@@ -0,0 +1,160 @@
+use crate::grade::grade;
+use crate::solver::Solver;
+use crate::strategy::Strategy;
+
+use std::cmp::Reverse;
+
+// Games still unsolved after this many guesses are given up on.
+pub const MAX_GUESSES: usize = 20;
+// How many guesses each `fuzz_clues` game takes.
+pub const FUZZ_GUESSES: usize = 3;
+
+#[derive(Clone, Debug)]
+pub struct Outcome {
+    pub answer: String,
+    // None if the solver gave up or ran out of candidates.
+    pub guesses: Option<usize>,
+}
+
+// Plays a game against `answer`, starting from its first letter as in Lingo.
+pub fn simulate(solver: &mut Solver, answer: &str) -> Outcome {
+    let first = answer.chars().next().expect("Answer is empty");
+    solver.reset(first);
+
+    let mut guesses = None;
+    for turn in 1..=MAX_GUESSES {
+        let guess = match solver.next_guess() {
+            Some(guess) => guess,
+            None => break,
+        };
+
+        if guess == answer {
+            guesses = Some(turn);
+            break;
+        }
+        solver.hint(&guess, &grade(&guess, answer));
+    }
+
+    Outcome {
+        answer: answer.to_string(),
+        guesses,
+    }
+}
+
+// Plays every answer in the solver's answer pool.
+pub fn benchmark(solver: &mut Solver) -> Vec<Outcome> {
+    let mut outcomes = Vec::new();
+    let _: Result<(), ()> = benchmark_each(solver, |outcome| {
+        outcomes.push(outcome);
+        Ok(())
+    });
+    outcomes
+}
+
+// Like `benchmark`, but hands each outcome to `each` as soon as its game
+// ends instead of collecting them. Stops at the first error.
+pub fn benchmark_each<E>(solver: &mut Solver, mut each: impl FnMut(Outcome) -> Result<(), E>) -> Result<(), E> {
+    let answers: Vec<String> = solver.all_answers.words.iter()
+        .map(|w| w.word.clone())
+        .collect();
+
+    for answer in &answers {
+        each(simulate(solver, answer))?;
+    }
+    Ok(())
+}
+
+// The `n` outcomes that took the most guesses, unsolved games first.
+pub fn hardest(mut outcomes: Vec<Outcome>, n: usize) -> Vec<Outcome> {
+    outcomes.sort_by_key(|o| Reverse(o.guesses.unwrap_or(MAX_GUESSES + 1)));
+    outcomes.truncate(n);
+    outcomes
+}
+
+// The mean guess count over solved games.
+pub fn average_guesses(outcomes: &[Outcome]) -> Option<f64> {
+    let solved: Vec<usize> = outcomes.iter().filter_map(|o| o.guesses).collect();
+    if solved.is_empty() {
+        return None;
+    }
+
+    Some(solved.iter().sum::<usize>() as f64 / solved.len() as f64)
+}
+
+// A game `fuzz_clues` found the answer filtered out of: the answer and each
+// guess with the feedback it got, up to the one that lost it.
+#[derive(Clone, Debug)]
+pub struct ClueFailure {
+    pub answer: String,
+    pub hints: Vec<(String, String)>,
+}
+
+// Plays `rounds` games of FUZZ_GUESSES random dictionary guesses against
+// random answers, through `Solver::reset` and `Solver::hint`, and checks
+// that the answer is never filtered out of the candidates. The same `seed`
+// plays the same games. The robust strategy would hide a lost answer by
+// recovering, and a trace only slows the games down, so both are turned off
+// for the run.
+pub fn fuzz_clues(solver: &mut Solver, rounds: usize, seed: u64) -> Result<(), ClueFailure> {
+    if solver.all_answers.words.is_empty() || solver.all_words.words.is_empty() {
+        return Ok(());
+    }
+    solver.strategy = Strategy::Frequency;
+    solver.config.trace = false;
+
+    let answers = solver.all_answers.words.clone();
+    let guesses = solver.all_words.words.clone();
+    let mut rng = XorShift::new(seed);
+    for _ in 0..rounds {
+        let answer = &answers[rng.below(answers.len())].word;
+        solver.reset(answer.chars().next().expect("Answer is empty"));
+
+        for _ in 0..FUZZ_GUESSES {
+            let guess = &guesses[rng.below(guesses.len())].word;
+            solver.hint(guess, &grade(guess, answer));
+
+            if !solver.valid_words.contains(answer) {
+                return Err(ClueFailure {
+                    answer: answer.clone(),
+                    hints: solver.history.iter()
+                        .map(|turn| (turn.guess.clone(), turn.feedback.clone()))
+                        .collect(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// A small xorshift generator, plenty for picking words.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> XorShift {
+        // Zero is the one state xorshift never leaves.
+        XorShift(seed.max(1))
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::dictionary::{Charset, Dictionary};
+
+    #[test]
+    fn fuzz_keeps_the_answer() {
+        let words = Dictionary::from_text("speed\nsteed\nsweed\nshred\nerase\nsassy\n", &Charset::default());
+        let mut solver = Solver::new(&Config::default(), words.clone(), words);
+
+        assert!(fuzz_clues(&mut solver, 500, 1).is_ok());
+    }
+}
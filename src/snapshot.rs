@@ -0,0 +1,148 @@
+use crate::clue::FirstLetter;
+use crate::error::{FieldError, LingoError};
+use crate::opener::fingerprint;
+use crate::solver::{GameSettings, Solver, Suggestion};
+use crate::strategy::Strategy;
+use crate::validate::{check, validate_feedback, validate_guess};
+
+use serde::{Deserialize, Serialize};
+
+// Bumped whenever the fields change, so old blobs are rejected rather than
+// misread.
+const VERSION: u32 = 2;
+
+// A game in a form another server can pick up: the reset and the hints it
+// took, not the candidates they left, which are found again by replaying
+// them. As a blob this is base64 of the JSON, about 180 bytes after a
+// reset and 25 more for each turn, so it fits in a header or a query
+// string. Facts added with `Solver::constrain` aren't kept.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    // `opener::fingerprint` of the word lists, which the importing server
+    // has to share.
+    pub dictionary: String,
+    pub letter: char,
+    pub mode: FirstLetter,
+    // The strategy the game was played with, whether the server's or one
+    // from `settings`.
+    pub strategy: Strategy,
+    pub settings: GameSettings,
+    // Each guess and its feedback, in order.
+    pub hints: Vec<(String, String)>,
+}
+
+impl Snapshot {
+    // The solver's current game, if it was started with a reset.
+    pub fn of(solver: &Solver) -> Option<Snapshot> {
+        Some(Snapshot {
+            version: VERSION,
+            dictionary: fingerprint(&solver.all_words, &solver.all_answers),
+            letter: solver.first_letter?,
+            mode: solver.first_letter_mode,
+            strategy: solver.strategy,
+            settings: solver.game_settings(),
+            hints: solver.history.iter()
+                .map(|turn| (turn.guess.clone(), turn.feedback.clone()))
+                .collect(),
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("Error serializing snapshot");
+        base64::encode(&json)
+    }
+
+    pub fn decode(blob: &str) -> Result<Snapshot, LingoError> {
+        let json = base64::decode(blob.trim()).map_err(|_| snapshot_error("isn't valid base64"))?;
+        let snapshot: Snapshot = serde_json::from_slice(&json).map_err(|_| snapshot_error("isn't a game snapshot"))?;
+
+        if snapshot.version != VERSION {
+            return Err(snapshot_error(format!("has version {} but this server reads version {}", snapshot.version, VERSION)));
+        }
+        Ok(snapshot)
+    }
+
+    // Replaces the solver's game with this one, after checking it was taken
+    // with the same word lists and that every hint is valid here. The hints
+    // aren't checked against each other, so a game that reached a dead end
+    // restores to the same dead end.
+    pub fn restore(&self, solver: &mut Solver) -> Result<Suggestion, LingoError> {
+        if self.dictionary != fingerprint(&solver.all_words, &solver.all_answers) {
+            return Err(snapshot_error("was taken with a different dictionary"));
+        }
+        self.settings.validate()?;
+
+        let mut errors = Vec::new();
+        for (idx, (guess, feedback)) in self.hints.iter().enumerate() {
+            validate_guess(&format!("hints[{}].guess", idx), guess, &solver.all_words, &mut errors);
+            validate_feedback(&format!("hints[{}].feedback", idx), feedback, &mut errors);
+        }
+        check(errors)?;
+
+        // A server with another strategy plays this game with the saved one,
+        // as if it had been passed in the settings.
+        let mut settings = self.settings.clone();
+        if self.strategy != solver.server_strategy() {
+            settings.strategy = Some(self.strategy);
+        }
+        solver.reset_with_settings(self.letter, self.mode, &settings);
+        for (guess, feedback) in &self.hints {
+            solver.hint(guess, feedback);
+        }
+
+        Ok(solver.suggestion())
+    }
+}
+
+fn snapshot_error(message: impl Into<String>) -> LingoError {
+    LingoError::Validation(vec![FieldError::new("snapshot", message)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::dictionary::{Charset, Dictionary};
+
+    fn solver() -> Solver {
+        let words = Dictionary::from_text("quart\nquate\nquake\nquail\nquiet\nqueen\n", &Charset::default());
+        Solver::new(&Config::default(), words.clone(), words)
+    }
+
+    #[test]
+    fn dead_end_game_restores_to_the_same_dead_end() {
+        let mut solver = solver();
+        solver.reset('q');
+        solver.hint("quart", "ccccc");
+        solver.hint("quate", "ccccc");
+        let blob = Snapshot::of(&solver).unwrap().encode();
+
+        let mut restored = solver.fresh();
+        let suggestion = Snapshot::decode(&blob).unwrap().restore(&mut restored).unwrap();
+
+        assert_eq!(suggestion.guess, None);
+        assert_eq!(suggestion.dead_end_turn, Some(2));
+        assert_eq!(restored.history.len(), 2);
+    }
+
+    #[test]
+    fn restore_keeps_the_saved_strategy() {
+        let mut solver = solver();
+        solver.strategy = Strategy::Minimax;
+        solver.reset('q');
+        solver.hint("quiet", "ccnnn");
+        let blob = Snapshot::of(&solver).unwrap().encode();
+
+        let mut restored = solver.fresh();
+        restored.strategy = Strategy::Frequency;
+        let suggestion = Snapshot::decode(&blob).unwrap().restore(&mut restored).unwrap();
+
+        assert_eq!(restored.strategy, Strategy::Minimax);
+        assert_eq!(suggestion.guess, solver.suggestion().guess);
+
+        // It's only this game's strategy.
+        restored.reset('q');
+        assert_eq!(restored.strategy, Strategy::Frequency);
+    }
+}
@@ -0,0 +1,1893 @@
+use crate::clue::{Clue, ConstraintSet, FirstLetter, Hint};
+use crate::config::Config;
+use crate::dictionary::{Dictionary, Word, WORD_LEN};
+use crate::error::{FieldError, LingoError};
+use crate::grade::{self, grade, partition};
+use crate::metrics::Metrics;
+use crate::opener::OpenerCache;
+use crate::strategy::{DistinguishScorer, FrequencyScorer, GuessStrategy, RarityScorer, RobustScorer, Strategy};
+use crate::validate::{check, validate_feedback, validate_guess, validate_spelling};
+use crate::weights::Weights;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+// Warnings from `Solver::best_ranked`, which every suggestion runs
+// through, are only printed the first time.
+static REPEATED_OPENER: Once = Once::new();
+static NO_FRESH_PROBE: Once = Once::new();
+
+// How many of the best next guesses a trace keeps.
+const TRACE_ALTERNATIVES: usize = 5;
+// How many times fewer candidates each guess leaves, on average, for
+// `Solver::estimated_turns_remaining`.
+const TURN_BRANCHING: f64 = 10.0;
+// Any letter in a template for `Solver::complete`.
+const TEMPLATE_WILDCARD: char = '_';
+// The most candidates `Solver::closest_pair` compares.
+const CLOSEST_PAIR_LIMIT: usize = 2000;
+// The most guesses `Solver::distinguishing_set` picks.
+const MAX_DISTINGUISHING_GUESSES: usize = 8;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Suggestion {
+    pub guess: Option<String>,
+    pub remaining: usize,
+    // The chance that the guess is the answer.
+    pub confidence: f64,
+    // Whether the confidence reaches `config.declare_threshold`, so the
+    // guess can be shown as the answer.
+    pub declared: bool,
+    // Set when no candidates are left, see `Solver::dead_end_turn`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_end_turn: Option<usize>,
+    // With `config.split_suggestion`, see `Solver::best_probe` and
+    // `Solver::best_bet`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_probe: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_bet: Option<String>,
+    // The strategy's pick when it was passed over for `guess` because
+    // every candidate gives it the same feedback.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uninformative_probe: Option<String>,
+}
+
+// The result of `Solver::turn`. A dead end shows as a suggestion with no
+// guess and `dead_end_turn` set.
+#[derive(Clone, Debug, Serialize)]
+pub struct TurnResult {
+    // Whether the guess was the answer.
+    pub solved: bool,
+    #[serde(flatten)]
+    pub suggestion: Suggestion,
+}
+
+// Guesses that together tell every candidate apart, see `Solver::distinguishing_set`.
+#[derive(Clone, Debug, Serialize)]
+pub struct DistinguishingSet {
+    pub guesses: Vec<String>,
+    // Whether the guesses tell every candidate apart. The search gives up
+    // before that when it runs out of guesses or time.
+    pub complete: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScoredWord {
+    pub word: String,
+    pub score: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Ranking {
+    pub guess: Option<String>,
+    pub remaining: usize,
+    pub ranked: Vec<ScoredWord>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Turn {
+    pub guess: String,
+    pub feedback: String,
+    pub before: usize,
+    pub after: usize,
+    pub eliminated: usize,
+}
+
+// One page of the remaining candidates.
+#[derive(Clone, Debug, Serialize)]
+pub struct RemainingPage {
+    pub total: usize,
+    pub offset: usize,
+    pub words: Vec<ScoredWord>,
+}
+
+// How much the suggested guess is expected to narrow the candidates.
+#[derive(Clone, Debug, Serialize)]
+pub struct Progress {
+    pub turn: usize,
+    pub guess: String,
+    pub remaining: usize,
+    pub expected_remaining: f64,
+    // See `Solver::estimated_turns_remaining`.
+    pub estimated_turns: f64,
+}
+
+// How a player's guess compares with the solver's, see `Solver::regret`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Regret {
+    pub guess: String,
+    pub expected_remaining: f64,
+    // Whether the solver would have considered the guess at all.
+    pub in_pool: bool,
+    pub best: String,
+    pub best_expected_remaining: f64,
+    // How many more candidates the guess is expected to leave than the
+    // solver's. Negative when the guess beats it.
+    pub regret: f64,
+}
+
+// The two candidates hardest to tell apart, see `Solver::closest_pair`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ClosestPair {
+    pub words: [String; 2],
+    pub distance: usize,
+    pub differences: Vec<PositionDifference>,
+    // Set when there were too many candidates to compare them all.
+    pub partial: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PositionDifference {
+    pub position: usize,
+    pub letters: [char; 2],
+}
+
+// What the solver did with the most recent hint.
+#[derive(Clone, Debug, Serialize)]
+pub struct Trace {
+    pub guess: String,
+    pub feedback: String,
+    pub eliminated: usize,
+    // Letters dropped from scoring by this hint, as pushed on the
+    // candidates' `ignore_letters`. That can include letters in the answer,
+    // like a letter both green and gray, and letters an earlier hint
+    // already dropped.
+    pub ignored: Vec<char>,
+    // The best next guesses, with their scores.
+    pub alternatives: Vec<ScoredWord>,
+}
+
+// Which word list guesses are drawn from, see `config.probe_source`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeSource {
+    // Every dictionary word, answers and other allowed guesses alike.
+    All,
+    // Only the answers.
+    Answers,
+    // Only the allowed guesses that aren't answers.
+    Allowed,
+}
+
+// Settings for one game in place of the server's, see
+// `Solver::reset_with_settings`. Unset fields keep the server's value.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GameSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<Strategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endgame_threshold: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opener_positional_weight: Option<u32>,
+}
+
+impl GameSettings {
+    pub fn validate(&self) -> Result<(), LingoError> {
+        let mut errors = Vec::new();
+
+        if self.opener_positional_weight == Some(0) {
+            errors.push(FieldError::new("opener_positional_weight", "must be at least 1"));
+        }
+
+        check(errors)
+    }
+}
+
+#[derive(Clone)]
+pub struct Solver {
+    pub config: Config,
+    pub all_words: Dictionary,
+    pub all_answers: Dictionary,
+    // The words guesses are drawn from, see `config.probe_pool`.
+    pub probe_words: Dictionary,
+    pub valid_words: Dictionary,
+    pub valid_guesses: Dictionary,
+    pub strategy: Strategy,
+    pub first_letter: Option<char>,
+    pub first_letter_mode: FirstLetter,
+    pub history: Vec<Turn>,
+    pub constraints: ConstraintSet,
+    // Only kept when `config.trace` is set.
+    pub trace: Option<Trace>,
+    // Kept across resets for `/metrics`.
+    pub metrics: Metrics,
+    // Precomputed first guesses, checked against the word lists on load.
+    pub opener_cache: Option<OpenerCache>,
+    // From `config.weights`, or empty to treat every word alike.
+    pub weights: Weights,
+    // The last `progress`, with the strategy it was computed for.
+    progress: Option<(Strategy, Progress)>,
+    // The values the current game's settings replaced, put back when it
+    // ends.
+    replaced: Option<ReplacedSettings>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ReplacedSettings {
+    strategy: Option<Strategy>,
+    endgame_threshold: Option<usize>,
+    // Set when the weight was replaced, with what it was before.
+    opener_positional_weight: Option<Option<u32>>,
+}
+
+impl Solver {
+    pub fn new(config: &Config, all_words: Dictionary, all_answers: Dictionary) -> Solver {
+        Solver {
+            config: config.clone(),
+            probe_words: probe_pool(config, &all_words, &all_answers),
+            all_words,
+            all_answers,
+            valid_words: Dictionary::empty(),
+            valid_guesses: Dictionary::empty(),
+            strategy: Strategy::Frequency,
+            first_letter: None,
+            first_letter_mode: FirstLetter::Green,
+            history: Vec::new(),
+            constraints: ConstraintSet::default(),
+            trace: None,
+            metrics: Metrics::default(),
+            opener_cache: None,
+            weights: Weights::default(),
+            progress: None,
+            replaced: None,
+        }
+    }
+
+    // Like `Solver::new` with this solver's config and word lists, without
+    // building the probe pool again.
+    pub fn fresh(&self) -> Solver {
+        let mut solver = Solver {
+            config: self.config.clone(),
+            all_words: self.all_words.clone(),
+            all_answers: self.all_answers.clone(),
+            probe_words: self.probe_words.clone(),
+            valid_words: Dictionary::empty(),
+            valid_guesses: Dictionary::empty(),
+            strategy: Strategy::Frequency,
+            first_letter: None,
+            first_letter_mode: FirstLetter::Green,
+            history: Vec::new(),
+            constraints: ConstraintSet::default(),
+            trace: None,
+            metrics: Metrics::default(),
+            opener_cache: self.opener_cache.clone(),
+            weights: self.weights.clone(),
+            progress: None,
+            replaced: self.replaced.clone(),
+        };
+        solver.end_settings();
+        solver.strategy = Strategy::Frequency;
+        solver
+    }
+
+    // Switches to another word list, ending the current game.
+    pub fn set_dictionary(&mut self, name: &str, all_words: Dictionary, all_answers: Dictionary) {
+        self.end_settings();
+        self.config.dictionary = name.to_string();
+        self.probe_words = probe_pool(&self.config, &all_words, &all_answers);
+        self.all_words = all_words;
+        self.all_answers = all_answers;
+        self.valid_words = Dictionary::empty();
+        self.valid_guesses = Dictionary::empty();
+        self.first_letter = None;
+        self.history.clear();
+        self.constraints = ConstraintSet::default();
+        self.trace = None;
+        self.opener_cache = None;
+        self.progress = None;
+    }
+
+    pub fn reset(&mut self, c: char) {
+        self.reset_with(c, FirstLetter::Green);
+    }
+
+    // Starts a new game, with `mode` deciding what `c` says about the answer.
+    pub fn reset_with(&mut self, c: char, mode: FirstLetter) {
+        self.end_settings();
+        let mut words = self.all_answers.clone();
+        let mut guesses = self.probe_words.clone();
+        self.constraints = ConstraintSet::default();
+
+        if let Some(clue) = Clue::first_letter(c, mode, WORD_LEN) {
+            words.filter(&clue);
+            guesses.filter(&clue);
+            self.constraints.add(&clue);
+        }
+
+        self.start(words, guesses);
+        self.first_letter = Some(c);
+        self.first_letter_mode = mode;
+    }
+
+    // Like `reset_with`, with `settings` in place of the server's until the
+    // next reset.
+    pub fn reset_with_settings(&mut self, c: char, mode: FirstLetter, settings: &GameSettings) {
+        self.reset_with(c, mode);
+        self.replaced = Some(self.apply_settings(settings));
+    }
+
+    // Applies the set fields of `settings`, returning the values they
+    // replaced.
+    fn apply_settings(&mut self, settings: &GameSettings) -> ReplacedSettings {
+        let mut replaced = ReplacedSettings::default();
+
+        if let Some(strategy) = settings.strategy {
+            replaced.strategy = Some(std::mem::replace(&mut self.strategy, strategy));
+        }
+        if let Some(threshold) = settings.endgame_threshold {
+            replaced.endgame_threshold = Some(std::mem::replace(&mut self.config.endgame_threshold, threshold));
+        }
+        if let Some(weight) = settings.opener_positional_weight {
+            replaced.opener_positional_weight = Some(self.config.opener_positional_weight.replace(weight));
+        }
+        self.progress = None;
+
+        replaced
+    }
+
+    // The settings the current game was reset with, as they'd be passed to
+    // `reset_with_settings` again.
+    pub fn game_settings(&self) -> GameSettings {
+        match &self.replaced {
+            Some(replaced) => GameSettings {
+                strategy: replaced.strategy.map(|_| self.strategy),
+                endgame_threshold: replaced.endgame_threshold.map(|_| self.config.endgame_threshold),
+                opener_positional_weight: replaced.opener_positional_weight.and(self.config.opener_positional_weight),
+            },
+            None => GameSettings::default(),
+        }
+    }
+
+    // The strategy of games that don't set their own.
+    pub fn server_strategy(&self) -> Strategy {
+        self.replaced.as_ref().and_then(|replaced| replaced.strategy).unwrap_or(self.strategy)
+    }
+
+    fn end_settings(&mut self) {
+        if let Some(replaced) = self.replaced.take() {
+            if let Some(strategy) = replaced.strategy {
+                self.strategy = strategy;
+            }
+            if let Some(threshold) = replaced.endgame_threshold {
+                self.config.endgame_threshold = threshold;
+            }
+            if let Some(weight) = replaced.opener_positional_weight {
+                self.config.opener_positional_weight = weight;
+            }
+        }
+    }
+
+    // Starts a game from constraints learned elsewhere, such as a shared
+    // compact string. Guesses have to keep a green first letter, as after
+    // a reset.
+    pub fn seed(&mut self, constraints: ConstraintSet) {
+        self.end_settings();
+        let mut words = self.all_answers.clone();
+        let mut guesses = self.probe_words.clone();
+
+        for clue in constraints.clues(WORD_LEN) {
+            words.filter(&clue);
+            if clue.hints[0] == Hint::Yes {
+                if let Some(first) = Clue::first_letter(clue.c, FirstLetter::Green, WORD_LEN) {
+                    guesses.filter(&first);
+                }
+            }
+        }
+
+        self.constraints = constraints;
+        self.start(words, guesses);
+        self.first_letter = None;
+        self.first_letter_mode = FirstLetter::Green;
+    }
+
+    fn start(&mut self, words: Dictionary, mut guesses: Dictionary) {
+        // Only the candidates can win, so a pruned probe pool gets them back.
+        if self.config.probe_pool.is_some() || self.config.probe_source != ProbeSource::All {
+            let probes: HashSet<&str> = guesses.words.iter().map(|w| w.word.as_str()).collect();
+            let missing: Vec<Word> = words.words.iter()
+                .filter(|w| !probes.contains(w.word.as_str()))
+                .cloned()
+                .collect();
+            guesses.words.extend(missing);
+        }
+
+        self.valid_words = words;
+        self.valid_guesses = guesses;
+        self.history.clear();
+        self.trace = None;
+        self.progress = None;
+    }
+
+    pub fn hint(&mut self, guess: &str, feedback: &str) {
+        let before = self.valid_words.words.len();
+        let ignored_before = self.valid_words.ignore_letters.len();
+
+        let clues = Clue::from_input(guess, feedback, WORD_LEN);
+        for clue in clues {
+            let clue = self.constraints.merge(&clue);
+            self.valid_words.filter(&clue);
+            self.constraints.add(&clue);
+        }
+
+        let after = self.valid_words.words.len();
+        self.history.push(Turn {
+            guess: guess.to_string(),
+            feedback: feedback.to_string(),
+            before,
+            after,
+            eliminated: before - after,
+        });
+        self.metrics.record_hint(before - after);
+        self.progress = None;
+
+        if self.strategy == Strategy::Robust && self.valid_words.words.is_empty() {
+            self.recover();
+        }
+
+        if self.config.trace {
+            self.trace = Some(Trace {
+                guess: guess.to_string(),
+                feedback: feedback.to_string(),
+                eliminated: before - after,
+                ignored: self.valid_words.ignore_letters[ignored_before..].to_vec(),
+                alternatives: self.ranking(TRACE_ALTERNATIVES).ranked,
+            });
+        }
+    }
+
+    // Applies a fact about how often `c` appears alongside the game's hints,
+    // without taking a turn.
+    pub fn constrain(&mut self, c: char, min: u32, max: Option<u32>) {
+        let clue = self.constraints.merge(&Clue {
+            c,
+            occur: min,
+            max,
+            hints: vec![Hint::Maybe; WORD_LEN],
+        });
+        self.valid_words.filter(&clue);
+        self.constraints.add(&clue);
+        self.progress = None;
+    }
+
+    // Validates and applies one guess and its feedback, then suggests the
+    // next guess.
+    pub fn turn(&mut self, guess: &str, feedback: &str) -> Result<TurnResult, LingoError> {
+        let mut errors = Vec::new();
+        validate_guess("guess", guess, &self.all_words, &mut errors);
+        validate_feedback("feedback", feedback, &mut errors);
+        check(errors)?;
+        self.check_feedback(guess, feedback)?;
+
+        self.hint(guess, feedback);
+
+        Ok(TurnResult {
+            solved: self.solved(),
+            suggestion: self.suggestion(),
+        })
+    }
+
+    // Goes back to how the game stood after `turn` hints, 0 being just after
+    // the reset, by starting it again and replaying the earlier hints. The
+    // game's own settings stay, but facts from `constrain` are dropped.
+    pub fn rewind(&mut self, turn: usize) -> Result<Suggestion, LingoError> {
+        let first = match self.first_letter {
+            Some(c) => c,
+            None => return Err(LingoError::Validation(vec![
+                FieldError::new("turn", "the game wasn't started with a reset"),
+            ])),
+        };
+        if turn > self.history.len() {
+            return Err(LingoError::Validation(vec![
+                FieldError::new("turn", format!("must be at most {}, the turns played so far", self.history.len())),
+            ]));
+        }
+
+        let hints: Vec<Turn> = self.history.drain(..turn).collect();
+        // Replayed hints were counted when they were first given.
+        let metrics = self.metrics.clone();
+        let replaced = self.replaced.take();
+
+        self.reset_with(first, self.first_letter_mode);
+        self.replaced = replaced;
+        for hint in &hints {
+            self.hint(&hint.guess, &hint.feedback);
+        }
+        self.metrics = metrics;
+
+        Ok(self.suggestion())
+    }
+
+    // Whether the last hint was all green.
+    pub fn solved(&self) -> bool {
+        self.history.last().is_some_and(|turn| turn.feedback.chars().all(|c| c == 'c'))
+    }
+
+    // In strict mode, checks that some dictionary word gives `feedback` for
+    // `guess`.
+    pub fn check_feedback(&self, guess: &str, feedback: &str) -> Result<(), LingoError> {
+        if !self.config.strict || self.all_words.words.iter().any(|w| grade(guess, &w.word) == feedback) {
+            return Ok(());
+        }
+
+        Err(LingoError::Validation(vec![
+            FieldError::new("feedback", format!("no dictionary word gives this feedback for {}", guess)),
+        ]))
+    }
+
+    // Checks a game sent whole before it's applied, as to /suggest: each
+    // turn's feedback must be what some dictionary word gives for its guess,
+    // and some word must match the reset and give every turn's feedback at
+    // once. Reports the first turn that fails as `field[i]`, whatever
+    // `config.strict` says. Replays and snapshots don't use it, since the
+    // games they carry may have reached a dead end.
+    pub fn validate_history(&self, reset: Option<(char, FirstLetter)>, field: &str, hints: &[(String, String)]) -> Result<(), LingoError> {
+        let reset = reset.and_then(|(c, mode)| Clue::first_letter(c, mode, WORD_LEN));
+        let mut consistent: Vec<&Word> = self.all_words.words.iter()
+            .filter(|w| reset.as_ref().is_none_or(|clue| w.has(clue)))
+            .collect();
+
+        for (idx, (guess, feedback)) in hints.iter().enumerate() {
+            let guess_field = format!("{}[{}].guess", field, idx);
+            let feedback_field = format!("{}[{}].feedback", field, idx);
+            let mut errors = Vec::new();
+            if guess.chars().count() != WORD_LEN {
+                errors.push(FieldError::new(&guess_field, format!("must be {} letters long", WORD_LEN)));
+            }
+            validate_feedback(&feedback_field, feedback, &mut errors);
+            check(errors)?;
+
+            if !self.all_words.words.iter().any(|w| grade(guess, &w.word) == *feedback) {
+                return Err(LingoError::Validation(vec![
+                    FieldError::new(&feedback_field, format!("no dictionary word gives this feedback for {}", guess)),
+                ]));
+            }
+
+            consistent.retain(|word| grade(guess, &word.word) == *feedback);
+            if consistent.is_empty() {
+                return Err(LingoError::Validation(vec![
+                    FieldError::new(&feedback_field, "contradicts the reset or the earlier turns: no dictionary word matches them all"),
+                ]));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Whether a replayed turn is well formed. Unlike `validate_history` it
+    // isn't checked against the earlier turns: without `strict`, or under
+    // the robust strategy, a game can hold turns that contradict each other,
+    // and it still has to replay to where it was exported.
+    fn validate_turn(&self, guess: &str, feedback: &str) -> Result<(), LingoError> {
+        let mut errors = Vec::new();
+        validate_spelling("guess", guess, &self.all_words.charset, &mut errors);
+        validate_feedback("feedback", feedback, &mut errors);
+        check(errors)
+    }
+
+    pub fn get_guess(&mut self) -> String {
+        match self.next_guess() {
+            Some(guess) => guess,
+            None => match self.dead_end_turn() {
+                Some(turn) => {
+                    let turn_info = &self.history[turn - 1];
+                    format!("No possible words! None were left after turn {} ({} {})", turn, turn_info.guess, turn_info.feedback)
+                },
+                None => String::from("No possible words!"),
+            },
+        }
+    }
+
+    // The turn, counting from 1, whose hint left no candidates. None while
+    // candidates remain, or if the reset itself left none.
+    pub fn dead_end_turn(&self) -> Option<usize> {
+        if !self.valid_words.words.is_empty() {
+            return None;
+        }
+
+        self.history.iter()
+            .position(|turn| turn.after == 0)
+            .map(|idx| idx + 1)
+    }
+
+    pub fn next_guess(&mut self) -> Option<String> {
+        self.informative_guess().0
+    }
+
+    // The strategy's guess, unless every candidate would give it the same
+    // feedback, which wastes the turn. Then it's the likeliest candidate
+    // instead, with the strategy's guess second. A configured opener is
+    // played either way.
+    fn informative_guess(&mut self) -> (Option<String>, Option<String>) {
+        match self.strategy_guess() {
+            Some(guess) if self.uninformative(&guess) && !self.fixed_opener_turn() => (self.best_bet(), Some(guess)),
+            guess => (guess, None),
+        }
+    }
+
+    // Whether `guess` can't tell any of the candidates apart.
+    pub fn uninformative(&self, guess: &str) -> bool {
+        self.valid_words.words.len() > 1 && partition(guess, &self.valid_words.words).len() == 1
+    }
+
+    // Whether this turn's guess is one of `config.openers`.
+    fn fixed_opener_turn(&self) -> bool {
+        self.history.len() < self.config.openers.len()
+    }
+
+    fn strategy_guess(&mut self) -> Option<String> {
+        if self.valid_words.words.is_empty() {
+            return None;
+        }
+        if let Some(guess) = self.forced_guess() {
+            return Some(guess);
+        }
+
+        self.rank_guesses();
+        Some(self.best_ranked())
+    }
+
+    // The top of the ranked guess pool, except that an opening guess with a
+    // repeated letter gives way to the best one without if
+    // `prefer_distinct_opener` is set.
+    fn best_ranked(&mut self) -> String {
+        let opening = self.history.is_empty();
+        let prefer_distinct = self.config.prefer_distinct_opener;
+        let candidates_only = self.valid_words.words.len() <= self.config.probe_threshold;
+        let candidates: HashSet<String> = if candidates_only {
+            self.valid_words.words.iter().map(|w| w.word.clone()).collect()
+        }
+        else {
+            HashSet::new()
+        };
+        let endgame = self.valid_words.words.len() <= self.config.endgame_threshold;
+        let rules = VariantRules::new(&self.config, &self.history, &self.constraints);
+        let pool = self.guess_pool();
+
+        let mut allowed: Vec<&Word> = pool.words.iter().filter(|w| rules.allows(w)).collect();
+        if allowed.is_empty() {
+            eprintln!("Warning: no guess follows the variant rules, ignoring them");
+            allowed = pool.words.iter().collect();
+        }
+        if !candidates_only && !endgame && rules.tested.is_some() {
+            let fresh: Vec<&Word> = allowed.iter().copied().filter(|w| rules.fresh(w)).collect();
+            if fresh.is_empty() {
+                NO_FRESH_PROBE.call_once(|| {
+                    eprintln!("No probe of untested letters is left, allowing tested ones; further times aren't reported");
+                });
+            }
+            else {
+                allowed = fresh;
+            }
+        }
+
+        if candidates_only {
+            if let Some(word) = allowed.iter().find(|w| candidates.contains(&w.word)) {
+                return word.word.clone();
+            }
+        }
+
+        let best = allowed[0];
+
+        if !opening || best.distinct_letters() {
+            return best.word.clone();
+        }
+
+        REPEATED_OPENER.call_once(|| {
+            eprintln!("Warning: opening guess {} repeats a letter, further ones aren't reported", best.word);
+        });
+        if prefer_distinct {
+            if let Some(word) = allowed.iter().find(|w| w.distinct_letters()) {
+                return word.word.clone();
+            }
+        }
+
+        best.word.clone()
+    }
+
+    // A guess that doesn't depend on scoring: the last candidate, one of the
+    // configured openers, or a cached first guess for this strategy.
+    fn forced_guess(&self) -> Option<String> {
+        if self.valid_words.words.len() == 1 {
+            return Some(self.valid_words.words[0].word.clone());
+        }
+        if let Some(opener) = self.config.openers.get(self.history.len()) {
+            return Some(opener.clone());
+        }
+
+        // The cache was computed with the server's settings, not this game's.
+        if self.replaced.is_some() {
+            return None;
+        }
+        let cache = self.opener_cache.as_ref()?;
+        if !self.history.is_empty() || self.first_letter_mode != FirstLetter::Green || cache.strategy != self.strategy.name() {
+            return None;
+        }
+        cache.openers.get(&self.first_letter?).cloned()
+    }
+
+    // Ranks the guess pool best first with the current strategy, returning
+    // the scores of the words in order. Strategies that only score part of
+    // the pool return fewer scores than there are words.
+    fn rank_guesses(&mut self) -> Vec<f64> {
+        match self.strategy {
+            Strategy::Frequency => self.rank_frequency(),
+            Strategy::Rarity => {
+                let scorer = RarityScorer::new(&self.valid_words);
+                self.guess_pool().rank_with(&scorer)
+            },
+            Strategy::Distinguish => {
+                let scorer = DistinguishScorer::new(&self.valid_words);
+                self.guess_pool().rank_with(&scorer)
+            },
+            Strategy::Entropy | Strategy::Minimax | Strategy::TwoMove => self.rank_partitions(),
+            Strategy::Robust => {
+                let scorer = RobustScorer::new(&self.robust_candidates());
+                self.guess_pool().rank_with(&scorer)
+            },
+        }
+    }
+
+    // The answers that fit the reset, each weighted 1 if it matches every
+    // feedback tile so far or `robust_near_miss_weight` if exactly one tile
+    // is off. Answers further off are left out.
+    fn robust_candidates(&self) -> Vec<(Word, f64)> {
+        let reset_clue = self.first_letter.and_then(|c| Clue::first_letter(c, self.first_letter_mode, WORD_LEN));
+        let near_miss = self.config.robust_near_miss_weight;
+
+        self.all_answers.words.iter()
+            .filter(|w| reset_clue.as_ref().is_none_or(|clue| w.has(clue)))
+            .filter_map(|w| match self.mismatched_tiles(&w.word) {
+                0 => Some((w.clone(), 1.0)),
+                1 => Some((w.clone(), near_miss)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // How many feedback tiles so far would differ if `answer` were the
+    // answer.
+    fn mismatched_tiles(&self, answer: &str) -> usize {
+        self.history.iter()
+            .map(|turn| {
+                grade(&turn.guess, answer).chars()
+                    .zip(turn.feedback.chars())
+                    .filter(|(expected, given)| expected != given)
+                    .count()
+            })
+            .sum()
+    }
+
+    // With no candidates left, assumes one feedback tile was wrong and takes
+    // the answers it would have ruled out as the candidates.
+    fn recover(&mut self) {
+        let near_misses: Vec<Word> = self.robust_candidates().into_iter()
+            .map(|(w, _)| w)
+            .collect();
+        if near_misses.is_empty() {
+            return;
+        }
+
+        eprintln!("No candidates left, assuming one feedback tile was wrong ({} candidates)", near_misses.len());
+        self.valid_words.words = near_misses;
+        self.constraints = ConstraintSet::default();
+    }
+
+    fn rank_frequency(&mut self) -> Vec<f64> {
+        let mut scorer = FrequencyScorer::new(&self.valid_words, self.config.score_mode, self.config.dead_letter_penalty)
+            .with_allowed_blend(&self.valid_words, &self.valid_guesses, self.config.allowed_frequency_blend);
+        if let Some(weight) = self.config.opener_positional_weight {
+            if self.history.is_empty() && self.strategy == Strategy::Frequency {
+                scorer = scorer.with_positional_weight(weight);
+            }
+        }
+        if !self.config.rare_letter_penalties.is_empty() && self.weights.is_empty() {
+            let required: Vec<char> = self.constraints.letters.iter()
+                .filter(|(_, letter)| letter.min > 0)
+                .map(|(&c, _)| c)
+                .collect();
+            scorer = scorer.with_rare_letter_penalties(&self.config.rare_letter_penalties, &required, &self.valid_words);
+        }
+        self.guess_pool().rank_with(&scorer)
+    }
+
+    // Ranks the guess pool with a strategy that isn't built in.
+    pub fn rank_custom(&mut self, strategy: &dyn GuessStrategy) -> Vec<f64> {
+        self.guess_pool().rank_with(strategy)
+    }
+
+    // Scores the best `candidate_cap` guesses by frequency on how they
+    // partition the candidates, giving up on the frequency ranking if that
+    // takes longer than `strategy_timeout_ms`.
+    fn rank_partitions(&mut self) -> Vec<f64> {
+        let strategy = self.strategy;
+        let cap = self.config.candidate_cap;
+        let timeout = Duration::from_millis(self.config.strategy_timeout_ms);
+        let candidates = self.valid_words.words.clone();
+
+        let frequency = self.rank_frequency();
+
+        let start = Instant::now();
+        let pool = self.guess_pool();
+        let mut scores = HashMap::new();
+
+        for word in pool.words.iter().take(cap) {
+            if start.elapsed() > timeout {
+                eprintln!("{} strategy timed out after {} guesses, using frequency scores", strategy.name(), scores.len());
+                return frequency;
+            }
+
+            let partitions = partition(&word.word, &candidates);
+            let score = match strategy {
+                Strategy::Minimax => -(grade::worst_case(&partitions) as f64),
+                Strategy::TwoMove => grade::two_move_chance(&partitions, candidates.len()),
+                _ => grade::entropy(&partitions, candidates.len()),
+            };
+            scores.insert(word.word.clone(), score);
+        }
+
+        pool.rank_head_by(cap, |w| scores[&w.word])
+    }
+
+    // The suggestion together with the best `limit` guesses and their scores.
+    pub fn ranking(&mut self, limit: usize) -> Ranking {
+        let remaining = self.valid_words.words.len();
+        if remaining == 0 {
+            return Ranking {
+                guess: None,
+                remaining,
+                ranked: Vec::new(),
+            };
+        }
+
+        let scores = self.rank_guesses();
+        let ranked: Vec<ScoredWord> = self.guess_pool().words.iter()
+            .zip(scores)
+            .take(limit)
+            .map(|(w, score)| ScoredWord {
+                word: w.word.clone(),
+                score,
+            })
+            .collect();
+
+        let guess = match self.forced_guess() {
+            Some(guess) => Some(guess),
+            None if ranked.is_empty() => None,
+            None => Some(self.best_ranked()),
+        };
+        let guess = match guess {
+            Some(guess) if self.uninformative(&guess) && !self.fixed_opener_turn() => self.best_bet(),
+            guess => guess,
+        };
+
+        Ranking {
+            guess,
+            remaining,
+            ranked,
+        }
+    }
+
+    // The candidates from `offset`, at most `limit` of them, ordered by
+    // frequency score and then alphabetically so pages don't overlap.
+    pub fn remaining_page(&self, offset: usize, limit: usize) -> RemainingPage {
+        let scorer = FrequencyScorer::new(&self.valid_words, self.config.score_mode, 0.0);
+        let mut scored: Vec<ScoredWord> = self.valid_words.words.iter()
+            .map(|w| ScoredWord {
+                word: w.word.clone(),
+                score: scorer.score(w),
+            })
+            .collect();
+        scored.sort_by(|a, b| b.score.partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.word.cmp(&b.word)));
+
+        RemainingPage {
+            total: scored.len(),
+            offset,
+            words: scored.into_iter().skip(offset).take(limit).collect(),
+        }
+    }
+
+    // Every guess scoring within `config.tie_epsilon` of the best, best
+    // first, for choosing between guesses the strategy can't tell apart.
+    pub fn ties(&mut self) -> Vec<ScoredWord> {
+        if self.valid_words.words.is_empty() {
+            return Vec::new();
+        }
+
+        let scores = self.rank_guesses();
+        let best = match scores.first() {
+            Some(&best) => best,
+            None => return Vec::new(),
+        };
+        let epsilon = self.config.tie_epsilon;
+
+        self.guess_pool().words.iter()
+            .zip(scores)
+            .take_while(|&(_, score)| best - score <= epsilon)
+            .map(|(w, score)| ScoredWord {
+                word: w.word.clone(),
+                score,
+            })
+            .collect()
+    }
+
+    // A small set of guesses whose combined feedback identifies the answer,
+    // found greedily from the `candidate_cap` best guesses by the
+    // distinguish strategy and as many of the candidates. It's approximate:
+    // a smaller set may exist, and the search stops at
+    // MAX_DISTINGUISHING_GUESSES or after `strategy_timeout_ms`.
+    pub fn distinguishing_set(&self) -> DistinguishingSet {
+        let cap = self.config.candidate_cap;
+        let mut pool = self.valid_guesses.clone();
+        pool.rank_with(&DistinguishScorer::new(&self.valid_words));
+        pool.words.truncate(cap);
+        pool.words.extend(self.valid_words.words.iter().take(cap).cloned());
+
+        let timeout = Duration::from_millis(self.config.strategy_timeout_ms);
+        let (guesses, complete) = grade::distinguishing_set(&pool.words, &self.valid_words.words, MAX_DISTINGUISHING_GUESSES, timeout);
+        DistinguishingSet {
+            guesses,
+            complete,
+        }
+    }
+
+    // The best guess by the current strategy with the letters of `template`
+    // in place, where TEMPLATE_WILDCARD stands for any letter.
+    pub fn complete(&mut self, template: &str) -> Result<String, LingoError> {
+        let mut errors = Vec::new();
+        if template.chars().count() != WORD_LEN {
+            errors.push(FieldError::new("template", format!("must be {} characters long", WORD_LEN)));
+        }
+        else if !template.chars().all(|c| c == TEMPLATE_WILDCARD || self.all_words.charset.allows(c)) {
+            errors.push(FieldError::new("template", format!("must only contain {} or {}", self.all_words.charset.describe(), TEMPLATE_WILDCARD)));
+        }
+        check(errors)?;
+
+        let mut clues: Vec<Clue> = Vec::new();
+        for (idx, c) in template.chars().enumerate().filter(|&(_, c)| c != TEMPLATE_WILDCARD) {
+            let clue = match clues.iter_mut().position(|clue| clue.c == c) {
+                Some(existing) => &mut clues[existing],
+                None => {
+                    clues.push(Clue {
+                        c,
+                        occur: 0,
+                        max: None,
+                        hints: vec![Hint::Maybe; WORD_LEN],
+                    });
+                    clues.last_mut().expect("Clue was just pushed")
+                },
+            };
+            clue.occur += 1;
+            clue.hints[idx] = Hint::Yes;
+        }
+
+        self.rank_guesses();
+        self.guess_pool().words.iter()
+            .find(|w| clues.iter().all(|clue| w.has(clue)))
+            .map(|w| w.word.clone())
+            .ok_or_else(|| LingoError::Validation(vec![
+                FieldError::new("template", "no guess matches it"),
+            ]))
+    }
+
+    // Compares `guess` with the solver's own suggestion by the candidates
+    // each is expected to leave, to be asked once the guess is made and
+    // before its feedback is applied. The guess can be any dictionary word,
+    // even one outside the guess pool. None when no candidates are left.
+    pub fn regret(&mut self, guess: &str) -> Result<Option<Regret>, LingoError> {
+        let mut errors = Vec::new();
+        validate_guess("guess", guess, &self.all_words, &mut errors);
+        check(errors)?;
+
+        let best = match self.next_guess() {
+            Some(best) => best,
+            None => return Ok(None),
+        };
+        let total = self.valid_words.words.len();
+        let expected = |word: &str| grade::expected_remaining(&partition(word, &self.valid_words.words), total);
+        let expected_remaining = expected(guess);
+        let best_expected_remaining = expected(&best);
+        let in_pool = self.guess_pool().contains(guess);
+
+        Ok(Some(Regret {
+            guess: guess.to_string(),
+            expected_remaining,
+            in_pool,
+            best,
+            best_expected_remaining,
+            regret: expected_remaining - best_expected_remaining,
+        }))
+    }
+
+    // The pair of candidates differing in the fewest positions, with the
+    // letters a probe could test to tell them apart. Every pair is
+    // compared, so only the first CLOSEST_PAIR_LIMIT candidates are.
+    pub fn closest_pair(&self) -> Option<ClosestPair> {
+        let (a, b, distance) = self.valid_words.closest_pair(CLOSEST_PAIR_LIMIT)?;
+
+        let differences = a.word.chars().zip(b.word.chars())
+            .enumerate()
+            .filter(|(_, (x, y))| x != y)
+            .map(|(position, (x, y))| PositionDifference {
+                position,
+                letters: [x, y],
+            })
+            .collect();
+
+        Some(ClosestPair {
+            words: [a.word.clone(), b.word.clone()],
+            distance,
+            differences,
+            partial: self.valid_words.words.len() > CLOSEST_PAIR_LIMIT,
+        })
+    }
+
+    // A guess after which every feedback leaves at most one candidate, so
+    // the game is sure to be won on the next guess. Candidates are tried
+    // first since they might win straight away.
+    pub fn guaranteed_finish(&self) -> Option<String> {
+        let candidates = &self.valid_words.words;
+        // Each candidate needs its own feedback, and there are only so many.
+        if candidates.is_empty() || candidates.len() > 3usize.pow(WORD_LEN as u32) {
+            return None;
+        }
+
+        candidates.iter()
+            .chain(&self.valid_guesses.words)
+            .find(|w| grade::worst_case(&partition(&w.word, candidates)) <= 1)
+            .map(|w| w.word.clone())
+    }
+
+    // The candidate whose feedback tells the other candidates apart best,
+    // by expected information in bits, for play where only a possible
+    // answer may be guessed. Like the entropy strategy it only evaluates
+    // the best `candidate_cap` by frequency score, and ties go to the
+    // better frequency score.
+    pub fn best_candidate_guess(&self) -> Option<ScoredWord> {
+        let mut candidates = self.valid_words.clone();
+        candidates.rank_with(&FrequencyScorer::new(&self.valid_words, self.config.score_mode, 0.0));
+        let total = self.valid_words.words.len();
+
+        let mut best: Option<ScoredWord> = None;
+        for word in candidates.words.iter().take(self.config.candidate_cap) {
+            let score = grade::entropy(&partition(&word.word, &self.valid_words.words), total);
+            if best.as_ref().is_none_or(|b| score > b.score) {
+                best = Some(ScoredWord {
+                    word: word.word.clone(),
+                    score,
+                });
+            }
+        }
+        best
+    }
+
+    // The words to guess from: the candidates once few are left, and also
+    // whenever the guesses have run out, so there's always a guess while
+    // any candidate remains.
+    fn guess_pool(&mut self) -> &mut Dictionary {
+        if self.valid_words.words.len() <= self.config.endgame_threshold || self.valid_guesses.words.is_empty() {
+            &mut self.valid_words
+        }
+        else {
+            &mut self.valid_guesses
+        }
+    }
+
+    pub fn check_openers(&self) -> Result<(), LingoError> {
+        let mut errors = Vec::new();
+
+        for (idx, opener) in self.config.openers.iter().enumerate() {
+            let field = format!("openers[{}]", idx);
+
+            if opener.chars().count() != WORD_LEN {
+                errors.push(FieldError::new(&field, format!("must be {} letters long", WORD_LEN)));
+            }
+            else if !self.all_words.contains(opener) {
+                errors.push(FieldError::new(&field, "is not in the dictionary"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        }
+        else {
+            Err(LingoError::Validation(errors))
+        }
+    }
+
+    // A rough guess at how many more guesses the game takes, counting the
+    // winning one: 1 for the last candidate, and 1 more for each factor of
+    // TURN_BRANCHING among the candidates beyond that. It only looks at the
+    // candidate count, not the strategy, and is fitted to the frequency
+    // strategy taking about 4 guesses for a thousand candidates.
+    pub fn estimated_turns_remaining(&self) -> f64 {
+        let remaining = self.valid_words.words.len();
+        if remaining == 0 {
+            return 0.0;
+        }
+
+        1.0 + (remaining as f64).ln() / TURN_BRANCHING.ln()
+    }
+
+    // The expected remaining candidates after the suggested guess, computed
+    // once per turn and strategy.
+    pub fn progress(&mut self) -> Option<Progress> {
+        if let Some((strategy, progress)) = &self.progress {
+            if *strategy == self.strategy {
+                return Some(progress.clone());
+            }
+        }
+
+        let guess = self.next_guess()?;
+        let total = self.valid_words.words.len();
+        let partitions = partition(&guess, &self.valid_words.words);
+        let progress = Progress {
+            turn: self.history.len() + 1,
+            guess,
+            remaining: total,
+            expected_remaining: grade::expected_remaining(&partitions, total),
+            estimated_turns: self.estimated_turns_remaining(),
+        };
+
+        self.progress = Some((self.strategy, progress.clone()));
+        Some(progress)
+    }
+
+    pub fn suggestion(&mut self) -> Suggestion {
+        let (guess, uninformative_probe) = self.informative_guess();
+        let confidence = guess.as_deref().map_or(0.0, |g| self.confidence(g));
+        let (best_probe, best_bet) = if self.config.split_suggestion {
+            (self.best_probe(guess.as_deref()), self.best_bet())
+        }
+        else {
+            (None, None)
+        };
+
+        Suggestion {
+            guess,
+            remaining: self.valid_words.words.len(),
+            confidence,
+            declared: confidence > 0.0 && confidence >= self.config.declare_threshold,
+            dead_end_turn: self.dead_end_turn(),
+            best_probe,
+            best_bet,
+            uninformative_probe,
+        }
+    }
+
+    // The strategy's pick from the whole guess pool, even in the endgame
+    // where the suggestion only picks from the candidates. Outside the
+    // endgame that's the suggested `guess`.
+    fn best_probe(&self, guess: Option<&str>) -> Option<String> {
+        if self.valid_words.words.len() > self.config.endgame_threshold {
+            return guess.map(String::from);
+        }
+
+        let mut solver = self.clone();
+        solver.config.endgame_threshold = 0;
+        solver.next_guess()
+    }
+
+    // The candidate most likely to be the answer, by `weights`, with ties
+    // going to the best frequency score among the candidates. Guessing it
+    // gives the best chance of winning this turn.
+    pub fn best_bet(&self) -> Option<String> {
+        self.ranked_candidates().into_iter().next().map(|(word, _)| word.word)
+    }
+
+    // Every candidate with its likelihood of being the answer, most likely
+    // first. With `weights` loaded the likelihood is the candidate's weight
+    // over the candidates' total, as in `confidence`, so they sum to 1 and
+    // ties keep frequency score order. Without them every candidate is as
+    // likely as the next, so the likelihood is its frequency score instead,
+    // which only orders them and doesn't sum to anything in particular.
+    // Unlike the strategy's ranking, probes that can't be the answer are
+    // never included.
+    pub fn ranked_candidates(&self) -> Vec<(Word, f64)> {
+        let mut candidates = self.valid_words.clone();
+        let scores = candidates.rank_with(&FrequencyScorer::new(&self.valid_words, self.config.score_mode, 0.0));
+        if self.weights.is_empty() {
+            return candidates.words.into_iter().zip(scores).collect();
+        }
+
+        let total: f64 = candidates.words.iter().map(|w| self.weights.get(&w.word)).sum();
+        let mut ranked: Vec<(Word, f64)> = candidates.words.into_iter()
+            .map(|w| {
+                let likelihood = if total == 0.0 { 0.0 } else { self.weights.get(&w.word) / total };
+                (w, likelihood)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+    }
+
+    // The chance that `guess` is the answer, weighing candidates by
+    // `weights`, or equally without them. 0 for a probe that isn't a
+    // candidate and 1 for the last candidate.
+    pub fn confidence(&self, guess: &str) -> f64 {
+        if !self.valid_words.contains(guess) {
+            return 0.0;
+        }
+        if self.weights.is_empty() {
+            return 1.0 / self.valid_words.words.len() as f64;
+        }
+
+        let total: f64 = self.valid_words.words.iter().map(|w| self.weights.get(&w.word)).sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        self.weights.get(guess) / total
+    }
+
+    // Swaps in new weights, dropping anything computed with the old ones.
+    pub fn set_weights(&mut self, weights: Weights) {
+        self.weights = weights;
+        self.progress = None;
+    }
+
+    // The first guess each strategy makes after a reset with `c`, using the
+    // opener cache where it applies. This game is left untouched.
+    pub fn openers(&self, c: char, mode: FirstLetter) -> BTreeMap<&'static str, Option<String>> {
+        Strategy::ALL.iter()
+            .map(|&strategy| {
+                let mut solver = self.fresh();
+                solver.strategy = strategy;
+                solver.reset_with(c, mode);
+                (strategy.name(), solver.next_guess())
+            })
+            .collect()
+    }
+
+    // What the solver would suggest if `feedback` came back for `guess`,
+    // leaving this game untouched.
+    pub fn preview(&self, guess: &str, feedback: &str) -> Suggestion {
+        let mut solver = self.clone();
+        solver.hint(guess, feedback);
+        solver.suggestion()
+    }
+
+    // Produces a script that `lingo pipe` replays into the same state. A
+    // game's own endgame threshold and opener weight are written after its
+    // reset mode as `name=value`; its own strategy is the `strategy` line.
+    // Facts added with `constrain` aren't turns, so they aren't written and
+    // a game that used them replays with more candidates.
+    pub fn export_replay(&self) -> String {
+        let mut script = format!("dictionary {} {}\n", self.config.dictionary, self.all_words.words.len());
+        script += &format!("strategy {}\n", self.strategy.name());
+
+        let settings = self.game_settings();
+        let mut overrides = String::new();
+        if let Some(threshold) = settings.endgame_threshold {
+            overrides += &format!(" endgame_threshold={}", threshold);
+        }
+        if let Some(weight) = settings.opener_positional_weight {
+            overrides += &format!(" opener_positional_weight={}", weight);
+        }
+        match (self.first_letter, self.first_letter_mode) {
+            (Some(c), FirstLetter::Green) if overrides.is_empty() => script += &format!("reset {}\n", c),
+            (Some(c), mode) => script += &format!("reset {} {}{}\n", c, mode.name(), overrides),
+            (None, _) => {},
+        }
+        for turn in &self.history {
+            script += &format!("{} {}\n", turn.guess, turn.feedback);
+        }
+
+        script
+    }
+
+    // Runs a replay script line by line, returning the suggestion printed
+    // after every reset and hint.
+    pub fn replay(&mut self, script: &str) -> Vec<String> {
+        let mut output = Vec::new();
+
+        for line in script.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+
+            match parts.as_slice() {
+                [] => {},
+                [comment, ..] if comment.starts_with('#') => {},
+                ["dictionary", name, len] => {
+                    if *name != self.config.dictionary || *len != self.all_words.words.len().to_string() {
+                        eprintln!("Warning: script was recorded with dictionary {} ({} words)", name, len);
+                    }
+                },
+                ["strategy", name] => match Strategy::from_name(name) {
+                    Some(strategy) => self.strategy = strategy,
+                    None => eprintln!("Warning: unknown strategy {}", name),
+                },
+                ["reset", letter] => match letter.chars().next() {
+                    Some(c) => {
+                        self.reset(c);
+                        output.push(self.get_guess());
+                    },
+                    None => eprintln!("Warning: missing reset letter"),
+                },
+                ["reset", letter, mode, overrides @ ..] => match (letter.chars().next(), FirstLetter::from_name(mode), parse_overrides(overrides)) {
+                    (Some(c), Some(mode), Some(settings)) => {
+                        if overrides.is_empty() {
+                            self.reset_with(c, mode);
+                        }
+                        else {
+                            self.reset_with_settings(c, mode, &settings);
+                        }
+                        output.push(self.get_guess());
+                    },
+                    _ => eprintln!("Warning: could not parse line: {}", line),
+                },
+                [guess, feedback] => match self.validate_turn(guess, feedback) {
+                    Ok(()) => {
+                        self.hint(guess, feedback);
+                        output.push(self.get_guess());
+                    },
+                    Err(err) => eprintln!("Warning: skipping {}: {}", line, err),
+                },
+                _ => eprintln!("Warning: could not parse line: {}", line),
+            }
+        }
+
+        output
+    }
+}
+
+// The `name=value` game settings after a replay script's reset mode, None
+// if any of them can't be read.
+fn parse_overrides(overrides: &[&str]) -> Option<GameSettings> {
+    let mut settings = GameSettings::default();
+
+    for token in overrides {
+        let (name, value) = token.split_once('=')?;
+        match name {
+            "endgame_threshold" => settings.endgame_threshold = Some(value.parse().ok()?),
+            "opener_positional_weight" => settings.opener_positional_weight = Some(value.parse().ok()?),
+            _ => return None,
+        }
+    }
+
+    settings.validate().ok()?;
+    Some(settings)
+}
+
+// The guesses a game variant forbids, from `config.no_repeat_guesses` and
+// `config.no_moved_greens`, and the probes `config.fresh_letter_probes`
+// prefers.
+struct VariantRules {
+    guessed: HashSet<String>,
+    // Each letter found green, with the positions it was green in.
+    greens: Vec<(char, Vec<usize>)>,
+    // The letters of every guess so far, when probes should avoid them.
+    tested: Option<HashSet<char>>,
+    // Every letter known to be green and where, which `tested` ignores.
+    placed: HashSet<(char, usize)>,
+}
+
+impl VariantRules {
+    fn new(config: &Config, history: &[Turn], constraints: &ConstraintSet) -> VariantRules {
+        let guessed = if config.no_repeat_guesses {
+            history.iter().map(|turn| turn.guess.clone()).collect()
+        }
+        else {
+            HashSet::new()
+        };
+        let greens = if config.no_moved_greens {
+            constraints.letters.iter()
+                .filter(|(_, letter)| !letter.greens.is_empty())
+                .map(|(&c, letter)| (c, letter.greens.clone()))
+                .collect()
+        }
+        else {
+            Vec::new()
+        };
+        let tested = if config.fresh_letter_probes {
+            Some(history.iter().flat_map(|turn| turn.guess.chars()).collect())
+        }
+        else {
+            None
+        };
+        let placed = constraints.letters.iter()
+            .flat_map(|(&c, letter)| letter.greens.iter().map(move |&idx| (c, idx)))
+            .collect();
+
+        VariantRules {
+            guessed,
+            greens,
+            tested,
+            placed,
+        }
+    }
+
+    // Whether `word` only spends tiles off its known greens on distinct
+    // letters that haven't been guessed yet. Always true without
+    // `config.fresh_letter_probes`.
+    fn fresh(&self, word: &Word) -> bool {
+        let tested = match &self.tested {
+            Some(tested) => tested,
+            None => return true,
+        };
+
+        let mut seen = HashSet::new();
+        word.word.chars().enumerate()
+            .filter(|&(idx, c)| !self.placed.contains(&(c, idx)))
+            .all(|(_, c)| !tested.contains(&c) && seen.insert(c))
+    }
+
+    fn allows(&self, word: &Word) -> bool {
+        if self.guessed.contains(&word.word) {
+            return false;
+        }
+
+        word.word.chars().enumerate().all(|(idx, c)| {
+            self.greens.iter().all(|(green, positions)| *green != c || positions.contains(&idx))
+        })
+    }
+}
+
+// The words of `probe_source`, or the `probe_pool` best of them by frequency
+// score against all the answers, in dictionary order.
+fn probe_pool(config: &Config, all_words: &Dictionary, all_answers: &Dictionary) -> Dictionary {
+    let mut probes = match config.probe_source {
+        ProbeSource::All => all_words.clone(),
+        ProbeSource::Answers => all_answers.clone(),
+        ProbeSource::Allowed => {
+            let answers: HashSet<&str> = all_answers.words.iter().map(|w| w.word.as_str()).collect();
+            let mut allowed = all_words.clone();
+            allowed.retain_matching(|w| !answers.contains(w.word.as_str()));
+            allowed
+        },
+    };
+    let limit = match config.probe_pool {
+        Some(limit) => limit,
+        None => return probes,
+    };
+
+    probes.rank_with(&FrequencyScorer::new(all_answers, config.score_mode, 0.0));
+    probes.words.truncate(limit);
+    probes.words.sort_by_key(|w| w.index);
+    probes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Charset;
+    use crate::opener::fingerprint;
+    use crate::strategy::ScoreMode;
+
+    fn dictionary(words: &[&str]) -> Dictionary {
+        Dictionary::from_text(&words.join("\n"), &Charset::default())
+    }
+
+    fn solver_with(config: Config, words: &[&str]) -> Solver {
+        let words = dictionary(words);
+        Solver::new(&config, words.clone(), words)
+    }
+
+    // A solver guessing from `words` and `answers` together.
+    fn solver_lists(config: Config, words: &[&str], answers: &[&str]) -> Solver {
+        Solver::new(&config, dictionary(&[words, answers].concat()), dictionary(answers))
+    }
+
+    fn solver(words: &[&str]) -> Solver {
+        solver_with(Config::default(), words)
+    }
+
+    const Q_WORDS: &[&str] = &["quart", "quate", "quake", "quail", "quiet", "queen"];
+
+    fn cached_solver() -> Solver {
+        let mut solver = solver(Q_WORDS);
+        let mut openers = BTreeMap::new();
+        openers.insert('q', String::from("queen"));
+        solver.opener_cache = Some(OpenerCache {
+            dictionary: fingerprint(&solver.all_words, &solver.all_answers),
+            strategy: String::from("frequency"),
+            openers,
+        });
+        solver
+    }
+
+    #[test]
+    fn opener_cache_is_skipped_with_game_settings() {
+        let mut solver = cached_solver();
+        solver.reset('q');
+        assert_eq!(solver.next_guess().as_deref(), Some("queen"));
+
+        let settings = GameSettings { opener_positional_weight: Some(1), ..GameSettings::default() };
+        solver.reset_with_settings('q', FirstLetter::Green, &settings);
+        assert_ne!(solver.next_guess().as_deref(), Some("queen"));
+    }
+
+    #[test]
+    fn replay_script_keeps_game_settings() {
+        let mut solver = solver(Q_WORDS);
+        let settings = GameSettings { endgame_threshold: Some(4), opener_positional_weight: Some(1), ..GameSettings::default() };
+        solver.reset_with_settings('q', FirstLetter::Green, &settings);
+
+        let script = solver.export_replay();
+        assert!(script.contains("reset q green endgame_threshold=4 opener_positional_weight=1"));
+
+        let mut replayed = solver.fresh();
+        replayed.replay(&script);
+        assert_eq!(replayed.config.endgame_threshold, 4);
+        assert_eq!(replayed.config.opener_positional_weight, Some(1));
+    }
+
+    #[test]
+    fn replay_skips_hints_of_the_wrong_length() {
+        let mut solver = solver(Q_WORDS);
+        let output = solver.replay("reset q\nquarts cccccn\nquiet ccnnc");
+
+        assert_eq!(solver.history.len(), 1);
+        assert_eq!(output.len(), 2);
+    }
+
+    #[test]
+    fn dead_end_game_replays_to_the_same_state() {
+        let mut solver = solver(Q_WORDS);
+        solver.reset('q');
+        solver.hint("quart", "ccccc");
+        solver.hint("quate", "ccccc");
+        assert_eq!(solver.dead_end_turn(), Some(2));
+
+        let mut replayed = solver.fresh();
+        let output = replayed.replay(&solver.export_replay());
+
+        assert_eq!(replayed.history.len(), 2);
+        assert_eq!(replayed.dead_end_turn(), Some(2));
+        assert_eq!(output.last().cloned(), Some(solver.get_guess()));
+    }
+
+    fn hints(turns: &[(&str, &str)]) -> Vec<(String, String)> {
+        turns.iter().map(|(g, f)| (g.to_string(), f.to_string())).collect()
+    }
+
+    fn error_field(result: Result<(), LingoError>) -> String {
+        match result {
+            Err(LingoError::Validation(errors)) => errors[0].field.clone(),
+            Ok(()) => panic!("expected a validation error"),
+        }
+    }
+
+    #[test]
+    fn validate_history_accepts_consistent_turns() {
+        let solver = solver(Q_WORDS);
+        let turns = hints(&[("quiet", "ccnnc"), ("quake", "cccnn")]);
+
+        assert!(solver.validate_history(Some(('q', FirstLetter::Green)), "hints", &turns).is_ok());
+    }
+
+    #[test]
+    fn validate_history_reports_the_contradicting_turn() {
+        let solver = solver(Q_WORDS);
+        let turns = hints(&[("quart", "ccccc"), ("quate", "ccccc")]);
+
+        assert_eq!(error_field(solver.validate_history(None, "history", &turns)), "history[1].feedback");
+    }
+
+    #[test]
+    fn validate_history_rejects_unreachable_feedback() {
+        let solver = solver(Q_WORDS);
+        let turns = hints(&[("quart", "nnnnn")]);
+
+        assert_eq!(error_field(solver.validate_history(None, "hints", &turns)), "hints[0].feedback");
+    }
+
+    #[test]
+    fn validate_history_checks_the_reset() {
+        let solver = solver(&["quart", "bingo"]);
+        let turns = hints(&[("quart", "nnnnn")]);
+
+        assert!(solver.validate_history(None, "hints", &turns).is_ok());
+        assert_eq!(error_field(solver.validate_history(Some(('q', FirstLetter::Green)), "hints", &turns)), "hints[0].feedback");
+        assert!(solver.validate_history(Some(('q', FirstLetter::Ignore)), "hints", &turns).is_ok());
+    }
+
+    #[test]
+    fn fixed_openers_ignore_feedback() {
+        let words = &["quart", "quate", "quake", "quail", "quiet", "queen", "quack", "quash", "quasi", "quays"];
+        let config = Config {
+            openers: vec![String::from("quiet"), String::from("queen")],
+            ..Config::default()
+        };
+
+        for answer in &["quack", "quash", "quasi"] {
+            let mut solver = solver_with(config.clone(), words);
+            solver.reset('q');
+            assert_eq!(solver.next_guess().as_deref(), Some("quiet"));
+            solver.hint("quiet", &grade("quiet", answer));
+            assert_eq!(solver.next_guess().as_deref(), Some("queen"));
+        }
+    }
+
+    #[test]
+    fn endgame_guesses_a_candidate() {
+        let mut solver = solver_lists(Config::default(), &["rakes", "rebar", "kebab"], &["baker", "faker"]);
+        solver.reset_with('a', FirstLetter::Ignore);
+        assert_eq!(solver.valid_words.words.len(), 2);
+
+        let guess = solver.next_guess().unwrap();
+        assert!(guess == "baker" || guess == "faker", "guessed {}", guess);
+    }
+
+    fn candidates(solver: &Solver) -> Vec<&str> {
+        solver.valid_words.words.iter().map(|w| w.word.as_str()).collect()
+    }
+
+    #[test]
+    fn reset_modes() {
+        let mut solver = solver(&["crane", "acorn", "bingo", "cabin"]);
+
+        solver.reset_with('c', FirstLetter::Green);
+        assert_eq!(candidates(&solver), vec!["crane", "cabin"]);
+
+        solver.reset_with('c', FirstLetter::Present);
+        assert_eq!(candidates(&solver), vec!["crane", "acorn", "cabin"]);
+
+        solver.reset_with('c', FirstLetter::Ignore);
+        assert_eq!(candidates(&solver), vec!["crane", "acorn", "bingo", "cabin"]);
+    }
+
+    #[test]
+    fn contradictory_hint_reports_its_turn() {
+        let mut solver = solver(Q_WORDS);
+        solver.reset('q');
+        solver.hint("quiet", &grade("quiet", "quart"));
+        assert_eq!(solver.dead_end_turn(), None);
+
+        // Claims the answer is "quake", which the first turn ruled out.
+        solver.hint("quake", "ccccc");
+        assert_eq!(solver.dead_end_turn(), Some(2));
+        assert_eq!(solver.suggestion().dead_end_turn, Some(2));
+    }
+
+    #[test]
+    fn robust_converges_past_one_wrong_tile() {
+        let words = &["quart", "quate", "quake", "quail", "quiet", "queen", "quack", "quash", "quasi", "quays"];
+        let answer = "quack";
+        let mut solver = solver(words);
+        solver.strategy = Strategy::Robust;
+        solver.reset('q');
+
+        let mut solved = false;
+        for turn in 0..8 {
+            let guess = solver.next_guess().expect("No guess left");
+            if guess == answer {
+                solved = true;
+                break;
+            }
+            let mut feedback: Vec<char> = grade(&guess, answer).chars().collect();
+            if turn == 0 {
+                // Mis-enter the last tile of the first turn.
+                feedback[4] = if feedback[4] == 'n' { 'c' } else { 'n' };
+            }
+            solver.hint(&guess, &feedback.into_iter().collect::<String>());
+            if turn == 0 {
+                assert!(!solver.valid_words.contains(answer));
+            }
+        }
+
+        assert!(solved);
+    }
+
+    #[test]
+    fn empty_guess_pool_falls_back_to_candidates() {
+        let mut solver = solver(Q_WORDS);
+        solver.reset('q');
+        solver.valid_guesses.words.clear();
+
+        let guess = solver.next_guess().expect("No guess with candidates left");
+        assert!(solver.valid_words.contains(&guess));
+    }
+
+    #[test]
+    fn opener_weight_changes_the_first_guess() {
+        // See `strategy::tests::score_modes_rank_differently`: "salta" wins
+        // the combined score and "rille" the positional one.
+        let answers = &["ansae", "loans", "rille", "salta"];
+        let extras = &["tears", "easer", "altos"];
+
+        let mut plain = solver_lists(Config::default(), extras, answers);
+        plain.reset_with('a', FirstLetter::Ignore);
+        assert_eq!(plain.next_guess().as_deref(), Some("salta"));
+
+        let config = Config { opener_positional_weight: Some(20), ..Config::default() };
+        let mut weighted = solver_lists(config, extras, answers);
+        weighted.reset_with('a', FirstLetter::Ignore);
+        assert_eq!(weighted.next_guess().as_deref(), Some("rille"));
+    }
+
+    #[test]
+    fn probe_threshold_boundary() {
+        // Three candidates that "vents" tells apart, see
+        // `strategy::tests::distinguish_tests_the_differing_letters`.
+        let guess_with = |probe_threshold| {
+            let config = Config { probe_threshold, ..Config::default() };
+            let mut solver = solver_lists(config, &["vents", "trace"], &["crane", "crate", "crave"]);
+            solver.strategy = Strategy::Distinguish;
+            solver.reset_with('c', FirstLetter::Ignore);
+            solver.next_guess().unwrap()
+        };
+
+        assert_eq!(guess_with(2), "vents");
+        assert!(["crane", "crate", "crave"].contains(&guess_with(3).as_str()));
+    }
+
+    fn word(s: &str) -> Word {
+        Word::new(s, WORD_LEN).unwrap()
+    }
+
+    // The variant rules after guessing "quiet" against "quart".
+    fn rules_after_quiet(config: Config) -> VariantRules {
+        let mut solver = solver_with(config, Q_WORDS);
+        solver.reset('q');
+        solver.hint("quiet", &grade("quiet", "quart"));
+        VariantRules::new(&solver.config, &solver.history, &solver.constraints)
+    }
+
+    #[test]
+    fn no_repeat_guesses_rule() {
+        let rules = rules_after_quiet(Config { no_repeat_guesses: true, ..Config::default() });
+        assert!(!rules.allows(&word("quiet")));
+        assert!(rules.allows(&word("quart")));
+
+        assert!(rules_after_quiet(Config::default()).allows(&word("quiet")));
+    }
+
+    #[test]
+    fn no_moved_greens_rule() {
+        // "quiet" left 'q', 'u' and 't' green.
+        let rules = rules_after_quiet(Config { no_moved_greens: true, ..Config::default() });
+        assert!(!rules.allows(&word("equal")));
+        assert!(!rules.allows(&word("tulip")));
+        assert!(rules.allows(&word("quart")));
+
+        assert!(rules_after_quiet(Config::default()).allows(&word("equal")));
+    }
+
+    #[test]
+    fn weighted_candidates_sum_to_one() {
+        let mut solver = solver(Q_WORDS);
+        solver.reset('q');
+        solver.set_weights(Weights::from_text("quart 6\nquake 2\n").unwrap());
+
+        let ranked = solver.ranked_candidates();
+        assert_eq!(ranked.len(), Q_WORDS.len());
+        assert_eq!(ranked[0].0.word, "quart");
+        assert!((ranked[0].1 - 0.5).abs() < 1e-9);
+        assert_eq!(ranked[1].0.word, "quake");
+        assert!((ranked.iter().map(|(_, likelihood)| likelihood).sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    // The probes after a 'q' reset, sorted.
+    fn probes_after_reset(source: ProbeSource) -> Vec<String> {
+        let config = Config { probe_source: source, ..Config::default() };
+        let mut solver = solver_lists(config, &["quips", "quota", "slate"], &["quart", "quake", "crane"]);
+        solver.reset('q');
+        let mut probes: Vec<String> = solver.valid_guesses.words.iter().map(|w| w.word.clone()).collect();
+        probes.sort();
+        probes
+    }
+
+    #[test]
+    fn probe_pool_follows_the_probe_source() {
+        assert_eq!(probes_after_reset(ProbeSource::All), vec!["quake", "quart", "quips", "quota"]);
+        assert_eq!(probes_after_reset(ProbeSource::Answers), vec!["quake", "quart"]);
+        // The candidates are added back to the other guesses.
+        assert_eq!(probes_after_reset(ProbeSource::Allowed), vec!["quake", "quart", "quips", "quota"]);
+    }
+
+    #[test]
+    fn allowed_probe_source_leaves_out_the_answers() {
+        let config = Config { probe_source: ProbeSource::Allowed, ..Config::default() };
+        let solver = solver_lists(config, &["quips", "quota", "slate"], &["quart", "quake", "crane"]);
+
+        let probes: Vec<&str> = solver.probe_words.words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(probes, vec!["quips", "quota", "slate"]);
+    }
+
+    #[test]
+    fn uninformative_probe_gives_way_to_a_candidate() {
+        // Scored on the whole pool's letters, "akers" wins on its 's' but
+        // has every candidate's letters out of place, so they all give it
+        // the same feedback.
+        let config = Config {
+            score_mode: ScoreMode::Presence,
+            allowed_frequency_blend: 1.0,
+            endgame_threshold: 0,
+            ..Config::default()
+        };
+        let mut solver = solver_lists(config, &["akers", "sssss"], &["baker", "faker", "waker"]);
+        solver.reset_with('a', FirstLetter::Ignore);
+
+        let suggestion = solver.suggestion();
+        assert_eq!(suggestion.uninformative_probe.as_deref(), Some("akers"));
+        assert!(solver.uninformative("akers"));
+        let guess = suggestion.guess.unwrap();
+        assert!(solver.valid_words.contains(&guess), "guessed {}", guess);
+    }
+
+    #[test]
+    fn best_candidate_splits_the_rest_unlike_the_frequency_pick() {
+        let mut solver = solver(&["baker", "faker", "maker", "taker", "fembt"]);
+        solver.reset_with('a', FirstLetter::Ignore);
+
+        // The common letters win on frequency, but leave the other "-aker"
+        // words together, where "fembt" tells every candidate apart.
+        let frequency = solver.next_guess().unwrap();
+        assert!(frequency.ends_with("aker"), "guessed {}", frequency);
+
+        let best = solver.best_candidate_guess().unwrap();
+        assert_eq!(best.word, "fembt");
+        assert_eq!(partition(&best.word, &solver.valid_words.words).len(), 5);
+        assert!(partition(&frequency, &solver.valid_words.words).len() < 5);
+    }
+
+    #[test]
+    fn distinct_opener_replaces_a_repeated_letter() {
+        let words = &["sells", "bells", "tells", "cells", "seals", "slate"];
+
+        let mut plain = solver(words);
+        plain.reset_with('s', FirstLetter::Ignore);
+        let repeated = plain.next_guess().unwrap();
+        assert!(!word(&repeated).distinct_letters(), "guessed {}", repeated);
+
+        let config = Config { prefer_distinct_opener: true, ..Config::default() };
+        let mut solver = solver_with(config, words);
+        solver.reset_with('s', FirstLetter::Ignore);
+        let guess = solver.next_guess().unwrap();
+        let best_distinct = solver.valid_guesses.words.iter().find(|w| w.distinct_letters()).unwrap();
+        assert_ne!(guess, repeated);
+        assert_eq!(guess, best_distinct.word);
+    }
+
+    #[test]
+    fn constrain_keeps_words_with_the_minimum_count() {
+        let mut solver = solver(Q_WORDS);
+        solver.reset('q');
+
+        solver.constrain('e', 2, None);
+        assert_eq!(candidates(&solver), vec!["queen"]);
+    }
+
+    #[test]
+    fn fresh_probes_fall_back_to_tested_letters() {
+        // After "rakes", every word puts the tested 'r' or 's' off a green.
+        let words = &["baker", "faker", "maker", "taker", "waker", "rakes"];
+        let guess_after_rakes = |fresh_letter_probes| {
+            let config = Config { fresh_letter_probes, ..Config::default() };
+            let mut solver = solver_with(config, words);
+            solver.reset_with('a', FirstLetter::Ignore);
+            solver.hint("rakes", &grade("rakes", "maker"));
+            assert_eq!(solver.valid_words.words.len(), 5);
+            let rules = VariantRules::new(&solver.config, &solver.history, &solver.constraints);
+            assert_eq!(solver.valid_guesses.words.iter().any(|w| rules.fresh(w)), !fresh_letter_probes);
+            solver.next_guess()
+        };
+
+        let guess = guess_after_rakes(true);
+        assert!(guess.is_some());
+        assert_eq!(guess, guess_after_rakes(false));
+    }
+}
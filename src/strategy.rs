@@ -0,0 +1,379 @@
+use crate::dictionary::{CharFrequency, Dictionary, LetterPresence, PositionLetters, Word, WORD_LEN};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Counts per candidate in a blended frequency table, see
+// `FrequencyScorer::with_allowed_blend`.
+const BLEND_RESOLUTION: f64 = 1000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    Frequency,
+    Rarity,
+    // Probes for the letters that tell the remaining candidates apart.
+    Distinguish,
+    // Maximizes the expected information of the feedback.
+    Entropy,
+    // Minimizes the most candidates the feedback can leave.
+    Minimax,
+    // Maximizes the chance of knowing the answer after this guess, for the
+    // last couple of turns, see `grade::two_move_chance`.
+    TwoMove,
+    // Experimental: allows for one mistaken feedback tile, see
+    // `RobustScorer`.
+    Robust,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 7] = [
+        Strategy::Frequency,
+        Strategy::Rarity,
+        Strategy::Distinguish,
+        Strategy::Entropy,
+        Strategy::Minimax,
+        Strategy::TwoMove,
+        Strategy::Robust,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Frequency => "frequency",
+            Strategy::Rarity => "rarity",
+            Strategy::Distinguish => "distinguish",
+            Strategy::Entropy => "entropy",
+            Strategy::Minimax => "minimax",
+            Strategy::TwoMove => "twomove",
+            Strategy::Robust => "robust",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        match name {
+            "frequency" => Some(Strategy::Frequency),
+            "rarity" => Some(Strategy::Rarity),
+            "distinguish" => Some(Strategy::Distinguish),
+            "entropy" => Some(Strategy::Entropy),
+            "minimax" => Some(Strategy::Minimax),
+            "twomove" => Some(Strategy::TwoMove),
+            "robust" => Some(Strategy::Robust),
+            _ => None,
+        }
+    }
+}
+
+// What the frequency strategy rewards in a guess.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreMode {
+    // Covering frequent letters, wherever they are in the guess.
+    Presence,
+    // Placing letters where they are most often found in the candidates.
+    Positional,
+    // Presence plus a bonus for each letter in a frequent position. This is
+    // the original scoring.
+    Combined,
+}
+
+// Scores guesses against one turn's candidates, higher being better. The
+// built-in strategies implement this on concrete types that the solver
+// matches on, so their scoring loops are monomorphized; a `&dyn
+// GuessStrategy` can be passed to `Solver::rank_custom` for anything else.
+pub trait GuessStrategy {
+    fn score(&self, guess: &Word) -> f64;
+}
+
+pub struct FrequencyScorer {
+    freq: CharFrequency,
+    mode: ScoreMode,
+    dead_letters: Vec<char>,
+    // Subtracted for each tile spent on a dead letter.
+    penalty: f64,
+    // Overrides `mode` with a combined score of this weight.
+    positional_weight: Option<u32>,
+    // Subtracted once for each of these letters in the guess.
+    rare_letters: Vec<(char, f64)>,
+    // How many counts in `freq` stand for one candidate, which the
+    // penalties are measured in.
+    scale: f64,
+}
+
+impl FrequencyScorer {
+    // `dead_letter_penalty` is a fraction of the candidate count, so 0.5
+    // costs a tile on a known-absent letter as much as a letter half the
+    // candidates contain earns.
+    pub fn new(candidates: &Dictionary, mode: ScoreMode, dead_letter_penalty: f64) -> FrequencyScorer {
+        FrequencyScorer {
+            freq: candidates.char_frequency(),
+            mode,
+            dead_letters: candidates.ignore_letters.clone(),
+            penalty: dead_letter_penalty * candidates.words.len() as f64,
+            positional_weight: None,
+            rare_letters: Vec::new(),
+            scale: 1.0,
+        }
+    }
+
+    // Counts letters as `blend` parts their share of `allowed` to 1 -
+    // `blend` parts their share of the candidates, rather than just the
+    // candidates. Letters the candidates ignore still count for nothing.
+    // The counts are scaled up by `BLEND_RESOLUTION` so small shares of a
+    // few candidates don't round away.
+    pub fn with_allowed_blend(mut self, candidates: &Dictionary, allowed: &Dictionary, blend: f64) -> FrequencyScorer {
+        if blend == 0.0 || allowed.words.is_empty() {
+            return self;
+        }
+
+        let candidate_total = candidates.words.len() as f64;
+        let allowed_total = allowed.words.len() as f64;
+        let allowed_freq = allowed.char_frequency();
+        let mut blended = CharFrequency::new();
+        for c in self.freq.keys().chain(allowed_freq.keys()) {
+            if blended.contains_key(c) {
+                continue;
+            }
+            let counts = (0..WORD_LEN).map(|idx| {
+                let candidate = self.freq.get(c).map_or(0.0, |f| f[idx] as f64);
+                let allowed = allowed_freq.get(c).map_or(0.0, |f| f[idx] as f64) * candidate_total / allowed_total;
+                ((1.0 - blend) * candidate + blend * allowed) * BLEND_RESOLUTION
+            });
+            blended.insert(*c, counts.map(|n| n.round() as u32).collect());
+        }
+        // `allowed` doesn't know which letters the hints ruled out.
+        for c in &candidates.ignore_letters {
+            if let Some(v) = blended.get_mut(c) {
+                *v = vec![0; WORD_LEN];
+            }
+        }
+
+        self.freq = blended;
+        self.scale = BLEND_RESOLUTION;
+        self
+    }
+
+    // Penalizes guesses containing the letters of `penalties`, each by its
+    // fraction of the candidate count. Letters in `required` are left
+    // alone, since the answer has them anyway.
+    pub fn with_rare_letter_penalties(mut self, penalties: &BTreeMap<char, f64>, required: &[char], candidates: &Dictionary) -> FrequencyScorer {
+        let total = candidates.words.len() as f64;
+        self.rare_letters = penalties.iter()
+            .filter(|(c, _)| !required.contains(c))
+            .map(|(&c, &penalty)| (c, penalty * total))
+            .collect();
+        self
+    }
+
+    // Scores like `ScoreMode::Combined`, but with a letter in its frequent
+    // position counting `weight` times instead of `POSITIONAL_WEIGHT`.
+    pub fn with_positional_weight(mut self, weight: u32) -> FrequencyScorer {
+        self.positional_weight = Some(weight);
+        self
+    }
+}
+
+impl GuessStrategy for FrequencyScorer {
+    #[inline]
+    fn score(&self, guess: &Word) -> f64 {
+        let score = match self.positional_weight {
+            Some(weight) => guess.weighted_score(&self.freq, weight),
+            None => guess.score(&self.freq, self.mode),
+        } as f64;
+        let score = score - self.scale * self.rare_letters.iter()
+            .filter(|(c, _)| guess.word.contains(*c))
+            .map(|(_, penalty)| penalty)
+            .sum::<f64>();
+        if self.penalty == 0.0 {
+            return score;
+        }
+
+        score - self.scale * self.penalty * guess.dead_tiles(&self.dead_letters) as f64
+    }
+}
+
+pub struct RarityScorer {
+    presence: LetterPresence,
+    total: u32,
+}
+
+impl RarityScorer {
+    pub fn new(candidates: &Dictionary) -> RarityScorer {
+        RarityScorer {
+            presence: candidates.letter_presence(),
+            total: candidates.words.len() as u32,
+        }
+    }
+}
+
+impl GuessStrategy for RarityScorer {
+    #[inline]
+    fn score(&self, guess: &Word) -> f64 {
+        guess.rarity_score(&self.presence, self.total) as f64
+    }
+}
+
+pub struct DistinguishScorer {
+    letters: PositionLetters,
+}
+
+impl DistinguishScorer {
+    pub fn new(candidates: &Dictionary) -> DistinguishScorer {
+        DistinguishScorer {
+            letters: candidates.distinguishing_letters(),
+        }
+    }
+}
+
+impl GuessStrategy for DistinguishScorer {
+    #[inline]
+    fn score(&self, guess: &Word) -> f64 {
+        guess.distinguish_score(&self.letters) as f64
+    }
+}
+
+// Scores like the presence score, but over candidates weighted by how well
+// they fit the feedback: words matching every tile weigh 1 and words one
+// tile off weigh less. Letters of the near misses still earn some score, so
+// guesses tend to test them again instead of trusting every tile.
+pub struct RobustScorer {
+    letters: HashMap<char, f64>,
+}
+
+impl RobustScorer {
+    pub fn new(candidates: &[(Word, f64)]) -> RobustScorer {
+        let mut letters = HashMap::new();
+
+        for (word, weight) in candidates {
+            let chars: HashSet<char> = word.word.chars().collect();
+            for c in chars {
+                *letters.entry(c).or_insert(0.0) += weight;
+            }
+        }
+
+        RobustScorer {
+            letters,
+        }
+    }
+}
+
+impl GuessStrategy for RobustScorer {
+    fn score(&self, guess: &Word) -> f64 {
+        let chars: HashSet<char> = guess.word.chars().collect();
+        chars.iter().filter_map(|c| self.letters.get(c)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Charset;
+    use crate::grade::partition;
+
+    fn dictionary(words: &[&str]) -> Dictionary {
+        Dictionary::from_text(&words.join("\n"), &Charset::default())
+    }
+
+    fn word(s: &str) -> Word {
+        Word::new(s, WORD_LEN).unwrap()
+    }
+
+    // The best word of `pool` by `strategy`.
+    fn top(pool: &Dictionary, strategy: &dyn GuessStrategy) -> String {
+        let mut pool = pool.clone();
+        pool.rank_with(strategy);
+        pool.words[0].word.clone()
+    }
+
+    // Candidates telling themselves apart only by their first letter, which
+    // each has a different one of.
+    const AKER: &[&str] = &["baker", "faker", "maker", "taker", "waker", "raker"];
+
+    #[test]
+    fn rarity_splits_an_ambiguous_set_further() {
+        let candidates = dictionary(AKER);
+        let pool = dictionary(&[AKER, &["fembt"]].concat());
+
+        let frequency = top(&pool, &FrequencyScorer::new(&candidates, ScoreMode::Combined, 0.0));
+        let rarity = top(&pool, &RarityScorer::new(&candidates));
+
+        assert_eq!(rarity, "fembt");
+        assert!(partition(&rarity, &candidates.words).len() > partition(&frequency, &candidates.words).len());
+    }
+
+    #[test]
+    fn allowed_blend_keeps_ignored_letters_at_zero() {
+        let mut candidates = dictionary(&["mired", "filed"]);
+        candidates.ignore_letters.extend(&['o', 'p', 'u']);
+        let allowed = dictionary(&["poupo", "mired", "filed"]);
+
+        let scorer = FrequencyScorer::new(&candidates, ScoreMode::Combined, 0.0)
+            .with_allowed_blend(&candidates, &allowed, 0.5);
+
+        assert_eq!(scorer.score(&word("poupo")), 0.0);
+        assert!(scorer.score(&word("mired")) > 0.0);
+    }
+
+    #[test]
+    fn score_modes_rank_differently() {
+        let candidates = dictionary(&["ansae", "loans", "rille", "salta"]);
+        let pool = dictionary(&["ansae", "loans", "rille", "salta", "tears", "easer", "altos"]);
+        let top_by = |mode| top(&pool, &FrequencyScorer::new(&candidates, mode, 0.0));
+
+        // "loans" covers the most frequent letters, "rille" has the most in
+        // their frequent places, and "salta" does best on both counted
+        // together.
+        assert_eq!(top_by(ScoreMode::Presence), "loans");
+        assert_eq!(top_by(ScoreMode::Positional), "rille");
+        assert_eq!(top_by(ScoreMode::Combined), "salta");
+    }
+
+    #[test]
+    fn distinguish_tests_the_differing_letters() {
+        let candidates = dictionary(&["crane", "crate", "crave"]);
+        let pool = dictionary(&["crane", "crate", "crave", "vents", "trace"]);
+
+        let guess = top(&pool, &DistinguishScorer::new(&candidates));
+
+        assert_eq!(guess, "vents");
+        assert_eq!(partition(&guess, &candidates.words).len(), 3);
+    }
+
+    #[test]
+    fn dead_letter_penalty_costs_a_wasted_tile() {
+        let mut candidates = dictionary(&["baker", "faker", "maker"]);
+        candidates.ignore_letters.push('o');
+        let dead = word("fembo");
+        let unused = word("femby");
+
+        let plain = FrequencyScorer::new(&candidates, ScoreMode::Combined, 0.0);
+        assert_eq!(plain.score(&dead), plain.score(&unused));
+
+        let penalized = FrequencyScorer::new(&candidates, ScoreMode::Combined, 0.5);
+        assert!(penalized.score(&dead) < penalized.score(&unused));
+        assert_eq!(penalized.score(&unused), plain.score(&unused));
+    }
+
+    #[test]
+    fn rare_letter_penalty_drops_an_obscure_word() {
+        // "jokes" wins on its second 'j' from "rajes"; "pokes" has the same
+        // letters otherwise.
+        let candidates = dictionary(&["jokes", "pokes", "rajes", "tokes", "cokes", "yokes"]);
+        let (jokes, pokes) = (word("jokes"), word("pokes"));
+        let mut penalties = BTreeMap::new();
+        penalties.insert('j', 0.3);
+
+        let plain = FrequencyScorer::new(&candidates, ScoreMode::Combined, 0.0);
+        assert_eq!(top(&candidates, &plain), "jokes");
+
+        let penalized = FrequencyScorer::new(&candidates, ScoreMode::Combined, 0.0)
+            .with_rare_letter_penalties(&penalties, &[], &candidates);
+        assert!(penalized.score(&jokes) < penalized.score(&pokes));
+        assert_ne!(top(&candidates, &penalized), "jokes");
+
+        // A letter the answer is known to have isn't penalized.
+        let required = FrequencyScorer::new(&candidates, ScoreMode::Combined, 0.0)
+            .with_rare_letter_penalties(&penalties, &['j'], &candidates);
+        assert_eq!(top(&candidates, &required), "jokes");
+    }
+}
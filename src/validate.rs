@@ -0,0 +1,87 @@
+use crate::dictionary::{Charset, Dictionary, WORD_LEN};
+use crate::error::{FieldError, LingoError};
+
+const FEEDBACK_CHARS: [char; 3] = ['c', 'w', 'n'];
+
+pub fn validate_guess(field: &str, guess: &str, dictionary: &Dictionary, errors: &mut Vec<FieldError>) {
+    let before = errors.len();
+    validate_spelling(field, guess, &dictionary.charset, errors);
+    if errors.len() == before && !dictionary.contains(guess) {
+        errors.push(FieldError::new(field, "is not in the dictionary"));
+    }
+}
+
+// Whether `word` could be a word of this game, in the dictionary or not.
+pub fn validate_spelling(field: &str, word: &str, charset: &Charset, errors: &mut Vec<FieldError>) {
+    if word.chars().count() != WORD_LEN {
+        errors.push(FieldError::new(field, format!("must be {} letters long", WORD_LEN)));
+    }
+    else if !word.chars().all(|c| charset.allows(c)) {
+        errors.push(FieldError::new(field, format!("must only contain {}", charset.describe())));
+    }
+}
+
+pub fn validate_feedback(field: &str, feedback: &str, errors: &mut Vec<FieldError>) {
+    if feedback.chars().count() != WORD_LEN {
+        errors.push(FieldError::new(field, format!("must be {} characters long", WORD_LEN)));
+    }
+    else if !feedback.chars().all(|c| FEEDBACK_CHARS.contains(&c)) {
+        errors.push(FieldError::new(field, "must only contain 'c', 'w' or 'n'"));
+    }
+}
+
+// Only the lengths, for routes that otherwise take a guess on trust. Hints
+// with a guess of the wrong length, or feedback that doesn't cover it,
+// can't be applied to the game.
+pub fn validate_lengths(guess: &str, feedback: &str) -> Result<(), LingoError> {
+    let mut errors = Vec::new();
+    let guess_len = guess.chars().count();
+    let feedback_len = feedback.chars().count();
+
+    if guess_len != WORD_LEN {
+        errors.push(FieldError::new("guess", format!("has {} letters but this game's words have {}", guess_len, WORD_LEN)));
+    }
+    if feedback_len != guess_len {
+        errors.push(FieldError::new("feedback", format!("has {} characters but the guess has {} letters", feedback_len, guess_len)));
+    }
+
+    check(errors)
+}
+
+pub fn check(errors: Vec<FieldError>) -> Result<(), LingoError> {
+    if errors.is_empty() {
+        Ok(())
+    }
+    else {
+        Err(LingoError::Validation(errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(result: Result<(), LingoError>) -> Vec<String> {
+        match result {
+            Err(LingoError::Validation(errors)) => errors.into_iter().map(|e| e.field).collect(),
+            Ok(()) => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn lengths_match_the_game() {
+        assert!(validate_lengths("crane", "ccnwn").is_ok());
+    }
+
+    #[test]
+    fn guess_of_another_length_is_rejected() {
+        assert_eq!(fields(validate_lengths("crater", "ccnwnn")), vec!["guess"]);
+        assert_eq!(fields(validate_lengths("cran", "ccnw")), vec!["guess"]);
+    }
+
+    #[test]
+    fn feedback_must_cover_the_guess() {
+        assert_eq!(fields(validate_lengths("crane", "ccnw")), vec!["feedback"]);
+        assert_eq!(fields(validate_lengths("crater", "ccnwn")), vec!["guess", "feedback"]);
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+// How common each word is, read from lines of a word and a weight. Words
+// missing from the file weigh 1.
+#[derive(Clone, Debug, Default)]
+pub struct Weights {
+    words: HashMap<String, f64>,
+}
+
+impl Weights {
+    pub fn load(path: &str) -> io::Result<Weights> {
+        let data = fs::read_to_string(path)?;
+        Weights::from_text(&data).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    // Fails on the first bad line, so half a file is never used.
+    pub fn from_text(data: &str) -> Result<Weights, String> {
+        let mut words = HashMap::new();
+
+        for (idx, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let (word, weight) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(word), Some(weight), None) => (word, weight),
+                _ => return Err(format!("line {} must be a word and a weight", idx + 1)),
+            };
+            let weight: f64 = match weight.parse() {
+                Ok(weight) if f64::is_finite(weight) && weight >= 0.0 => weight,
+                _ => return Err(format!("line {} has an invalid weight {}", idx + 1, weight)),
+            };
+            words.insert(word.to_string(), weight);
+        }
+
+        Ok(Weights { words })
+    }
+
+    pub fn get(&self, word: &str) -> f64 {
+        self.words.get(word).cloned().unwrap_or(1.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
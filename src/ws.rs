@@ -0,0 +1,133 @@
+use crate::clue::FirstLetter;
+use crate::error::{FieldError, LingoError};
+use crate::solver::Solver;
+
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws::{Codec, Frame, Message};
+use actix_web::error::PayloadError;
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+// What a client sends over `/ws`, as JSON text messages like
+// {"type": "reset", "letter": "a"} or
+// {"type": "hint", "guess": "aries", "feedback": "cnnnn"}.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    Reset {
+        letter: char,
+        mode: Option<FirstLetter>,
+    },
+    Hint {
+        guess: String,
+        feedback: String,
+    },
+}
+
+#[derive(Serialize)]
+struct ErrorFrame {
+    errors: Vec<FieldError>,
+}
+
+// A game that lives as long as its connection, with the solver owned by
+// the connection instead of the server. Each message gets a suggestion
+// back, or an error message that leaves the socket open.
+pub struct Session {
+    solver: Solver,
+    codec: Codec,
+    buffer: BytesMut,
+    closed: bool,
+}
+
+impl Session {
+    pub fn new(solver: Solver) -> Session {
+        Session {
+            solver,
+            codec: Codec::new(),
+            buffer: BytesMut::new(),
+            closed: false,
+        }
+    }
+
+    // Turns the request body into the response body: incoming frames are
+    // decoded as they arrive and the replies encoded in their place. The
+    // solver is dropped with the stream when the client disconnects.
+    pub fn run<S>(mut self, payload: S) -> impl Stream<Item = Result<Bytes, PayloadError>>
+    where S: Stream<Item = Result<Bytes, PayloadError>>
+    {
+        payload.map(move |chunk| {
+            let chunk = chunk?;
+            let mut out = BytesMut::new();
+            if !self.closed {
+                self.buffer.extend_from_slice(&chunk);
+                self.receive(&mut out);
+            }
+            Ok(out.freeze())
+        })
+    }
+
+    fn receive(&mut self, out: &mut BytesMut) {
+        loop {
+            let frame = match self.codec.decode(&mut self.buffer) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return,
+                Err(err) => {
+                    // The framing is broken, so nothing after this can be read.
+                    self.send(Message::Close(None), out);
+                    self.closed = true;
+                    eprintln!("Closing websocket: {}", err);
+                    return;
+                },
+            };
+
+            match frame {
+                Frame::Text(text) => {
+                    let reply = self.reply(&text);
+                    self.send(Message::Text(reply), out);
+                },
+                Frame::Ping(data) => self.send(Message::Pong(data), out),
+                Frame::Pong(_) => {},
+                Frame::Close(reason) => {
+                    self.send(Message::Close(reason), out);
+                    self.closed = true;
+                    return;
+                },
+                Frame::Binary(_) | Frame::Continuation(_) => {
+                    let reply = error_frame("message", "must be a single text frame");
+                    self.send(Message::Text(reply), out);
+                },
+            }
+        }
+    }
+
+    fn reply(&mut self, text: &[u8]) -> String {
+        let message: ClientMessage = match serde_json::from_slice(text) {
+            Ok(message) => message,
+            Err(err) => return error_frame("message", err.to_string()),
+        };
+
+        let reply = match message {
+            ClientMessage::Reset { letter, mode } => {
+                self.solver.reset_with(letter, mode.unwrap_or(FirstLetter::Green));
+                serde_json::to_string(&self.solver.suggestion())
+            },
+            ClientMessage::Hint { guess, feedback } => match self.solver.turn(&guess, &feedback) {
+                Ok(result) => serde_json::to_string(&result),
+                Err(LingoError::Validation(errors)) => serde_json::to_string(&ErrorFrame { errors }),
+            },
+        };
+        reply.expect("Error serializing reply")
+    }
+
+    fn send(&mut self, message: Message, out: &mut BytesMut) {
+        if let Err(err) = self.codec.encode(message, out) {
+            eprintln!("Could not encode websocket reply: {}", err);
+        }
+    }
+}
+
+fn error_frame(field: &str, message: impl Into<String>) -> String {
+    let errors = vec![FieldError::new(field, message)];
+    serde_json::to_string(&ErrorFrame { errors }).expect("Error serializing reply")
+}